@@ -100,5 +100,37 @@ fn day7(c: &mut Criterion) {
     bench_func!(group, "Part2", part2, &input);
 }
 
-criterion_group!(benches, day1, day3, day5, day6, day7);
+fn day16(c: &mut Criterion) {
+    use adventofcode2019::day16::*;
+    let input = include_str!("../../input/16-1.txt");
+    let mut group = c.benchmark_group("Day 16");
+    let digits: Vec<i32> = input
+        .trim()
+        .chars()
+        .map(|c| c.to_digit(10).unwrap() as i32)
+        .collect();
+
+    for &phases in [1, 10, 100].iter() {
+        group.bench_with_input(BenchmarkId::new("naive", phases), &phases, |b, &p| {
+            b.iter(|| fft_naive(black_box(digits.clone()), p))
+        });
+        group.bench_with_input(BenchmarkId::new("prefix", phases), &phases, |b, &p| {
+            b.iter(|| fft(black_box(digits.clone()), 0, p))
+        });
+    }
+    // the whole 10,000 times repeated signal, as if the message offset
+    // were 0
+    let signal = digits.repeat(10_000);
+    group.sample_size(10);
+    group.bench_function("prefix/full signal", |b| {
+        b.iter(|| fft(black_box(signal.clone()), 0, 1))
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("parallel/full signal", |b| {
+        b.iter(|| fft_par(black_box(signal.clone()), 0, 1))
+    });
+    bench_func!(group, "Part2", part2, &input);
+}
+
+criterion_group!(benches, day1, day3, day5, day6, day7, day16);
 criterion_main!(benches);
@@ -0,0 +1,157 @@
+//! Known puzzle answers, used to check that solutions still give the
+//! same results after a change.
+//!
+//! Answers are kept in a plain text file with one answer per line,
+//! keyed by day and part:
+//!
+//! ```text
+//! # day-part answer
+//! 01-1 3289802
+//! 01-2 4931831
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::solution::Answer;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line the error was found in.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// How an answer compares to the one on record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    Match,
+    Mismatch { expected: String },
+    Missing,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Answers {
+    known: BTreeMap<(u32, u32), String>,
+}
+
+impl Answers {
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut answers = Answers::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: &str| ParseError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+            let (key, answer) = match line.find(char::is_whitespace) {
+                Some(i) => (&line[..i], line[i..].trim()),
+                None => return Err(err("missing answer")),
+            };
+            let mut parts = key.split('-').map(|n| n.parse::<u32>());
+            let (day, part) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(Ok(day)), Some(Ok(part @ 1..=2)), None) => (day, part),
+                _ => return Err(err("expected a key like `05-1`")),
+            };
+            if answers
+                .known
+                .insert((day, part), answer.to_string())
+                .is_some()
+            {
+                return Err(err("answer recorded twice"));
+            }
+        }
+        Ok(answers)
+    }
+
+    /// Loads answers from a file. A file that doesn't exist has no
+    /// answers in it.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => Answers::parse(&s).map_err(|e| io::Error::new(ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.known.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn check(&self, day: u32, part: u32, answer: &Answer) -> Check {
+        match self.get(day, part) {
+            Some(expected) if expected == answer.to_string() => Check::Match,
+            Some(expected) => Check::Mismatch {
+                expected: expected.to_string(),
+            },
+            None => Check::Missing,
+        }
+    }
+
+    /// Records an answer, returning the one it replaced, if any.
+    pub fn record(&mut self, day: u32, part: u32, answer: &Answer) -> Option<String> {
+        self.known.insert((day, part), answer.to_string())
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# day-part answer")?;
+        for ((day, part), answer) in self.known.iter() {
+            writeln!(f, "{:02}-{} {}", day, part, answer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("# comment\n01-1 3289802\n\n08-2 CEKUA\n").unwrap();
+        assert_eq!(Some("3289802"), answers.get(1, 1));
+        assert_eq!(None, answers.get(1, 2));
+        assert_eq!(Check::Match, answers.check(8, 2, &"CEKUA".into()));
+        assert_eq!(Check::Missing, answers.check(8, 1, &1548usize.into()));
+        assert_eq!(
+            Check::Mismatch {
+                expected: "3289802".to_string()
+            },
+            answers.check(1, 1, &3289803.into())
+        );
+        assert_eq!(
+            answers.to_string(),
+            Answers::parse(&answers.to_string()).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(2, Answers::parse("01-1 1\n01-3 2").unwrap_err().line);
+        assert_eq!(1, Answers::parse("01-1").unwrap_err().line);
+        assert_eq!(2, Answers::parse("01-1 1\n01-1 2").unwrap_err().line);
+    }
+}
@@ -0,0 +1,115 @@
+//! Interactive terminal for ASCII-capable Intcode programs (days 17,
+//! 21 and 25).
+//!
+//! Usage: `intcode-term <program> [--script <file>] [--history <file>]`
+//!
+//! Lines from the script file are sent first, one per input request,
+//! and echoed as if they had been typed. After the script runs out,
+//! input is read interactively with line editing and command history.
+//! Outputs outside of the ASCII range (e.g. the final dust count of
+//! day 17) are shown on their own line instead of as garbled chars.
+
+use adventofcode2019::intcode::{AsciiEvent, AsciiIo, IntcodeMachine, RunResult};
+use rustyline::{error::ReadlineError, Editor};
+use std::{collections::VecDeque, fs, process};
+
+const PROMPT: &str = "> ";
+
+struct Args {
+    program: String,
+    script: Option<String>,
+    history: Option<String>,
+}
+
+fn usage() -> ! {
+    eprintln!("usage: intcode-term <program> [--script <file>] [--history <file>]");
+    process::exit(2);
+}
+
+fn parse_args() -> Args {
+    let mut args = std::env::args().skip(1);
+    let mut program = None;
+    let mut script = None;
+    let mut history = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--script" => script = Some(args.next().unwrap_or_else(|| usage())),
+            "--history" => history = Some(args.next().unwrap_or_else(|| usage())),
+            "-h" | "--help" => usage(),
+            _ if program.is_none() => program = Some(arg),
+            _ => usage(),
+        }
+    }
+    Args {
+        program: program.unwrap_or_else(|| usage()),
+        script,
+        history,
+    }
+}
+
+fn read_file(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("could not read {}: {}", path, e);
+        process::exit(1);
+    })
+}
+
+/// Prints everything the program has output so far. Values that
+/// aren't ASCII chars are put on a line of their own so they can't be
+/// mistaken for part of the text.
+fn print_output(io: &mut AsciiIo) {
+    io.flush();
+    for event in io.drain_events() {
+        match event {
+            AsciiEvent::Line(line) => println!("{}", line),
+            AsciiEvent::Value(v) => println!("[non-ASCII output: {}]", v),
+        }
+    }
+}
+
+fn main() {
+    let args = parse_args();
+    let mut machine = IntcodeMachine::from_str(&read_file(&args.program));
+    let mut script: VecDeque<String> = match &args.script {
+        Some(path) => read_file(path).lines().map(String::from).collect(),
+        None => VecDeque::new(),
+    };
+
+    let mut editor = Editor::<()>::new();
+    if let Some(path) = &args.history {
+        // a missing history file just means nothing has been saved yet
+        let _ = editor.load_history(path);
+    }
+
+    let mut io = AsciiIo::new();
+    loop {
+        let result = io.run(&mut machine);
+        print_output(&mut io);
+        if let RunResult::Stop = result {
+            println!("[program halted]");
+            break;
+        }
+        let line = if let Some(line) = script.pop_front() {
+            println!("{}{}", PROMPT, line);
+            line
+        } else {
+            match editor.readline(PROMPT) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+                Err(e) => {
+                    eprintln!("could not read input: {}", e);
+                    break;
+                }
+            }
+        };
+        editor.add_history_entry(line.as_str());
+        io.push_str(&line);
+        io.push_str("\n");
+    }
+
+    if let Some(path) = &args.history {
+        if let Err(e) = editor.save_history(path) {
+            eprintln!("could not save history to {}: {}", path, e);
+        }
+    }
+}
@@ -43,3 +43,5 @@ pub fn part2(input: &str) -> i32 {
         .map(total_fuel)
         .sum()
 }
+
+impl_solution!(Day1, 1);
@@ -1,10 +1,10 @@
 //use util::Point;
-use crate::util::{gcd, Point};
+use crate::util::{num::gcd, Grid, Point};
 use std::collections::HashMap;
 
-fn simplify(x: i32, y: i32) -> (i32, i32) {
-    let divisor = gcd(x as i64, y as i64).abs();
-    (x / divisor as i32, y / divisor as i32)
+fn simplify(p: Point) -> Point {
+    let divisor = gcd(p.x as i64, p.y as i64) as i32;
+    Point::new(p.x / divisor, p.y / divisor)
 }
 
 // Using the same function for part 1 and part 2, so this needs to
@@ -13,29 +13,17 @@ fn simplify(x: i32, y: i32) -> (i32, i32) {
 // angles have any visible asteroids at all so for a part1-only
 // solution it would be more space efficient to just use a hashset of
 // angles.
-fn get_visible<'a>(asteroid: &Point, field: &'a [Point]) -> HashMap<(i32, i32), Vec<Point>> {
-    let mut vis: HashMap<(_, _), Vec<Point>> = HashMap::new();
+fn get_visible(asteroid: &Point, field: &[Point]) -> HashMap<Point, Vec<Point>> {
+    let mut vis: HashMap<Point, Vec<Point>> = HashMap::new();
     for other in field.iter().filter(|&o| o != asteroid) {
-        let angle = simplify(other.x - asteroid.x, other.y - asteroid.y);
-        vis.entry(angle).or_default().push(other.clone());
+        let angle = simplify(*other - *asteroid);
+        vis.entry(angle).or_default().push(*other);
     }
     vis
 }
 
 fn parse_asteroids(input: &str) -> Vec<Point> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            l.chars().enumerate().filter_map(move |(x, c)| {
-                if c == '#' {
-                    Some(Point::new(x as i32, y as i32))
-                } else {
-                    None
-                }
-            })
-        })
-        .collect()
+    Grid::parse(input, |c| c == '#').find_all(&true).collect()
 }
 
 pub fn part1(input: &str) -> usize {
@@ -56,9 +44,9 @@ pub fn part2(input: &str) -> i32 {
         .map(|a| (a, get_visible(a, &asteroids)))
         .max_by_key(|(_, v)| v.len())
         .unwrap();
-    let mut angles: Vec<((i32, i32), f32)> = Vec::new();
+    let mut angles: Vec<(Point, f32)> = Vec::new();
     for angle in visibility.keys() {
-        let atan2 = (angle.1 as f32).atan2(angle.0 as f32);
+        let atan2 = (angle.y as f32).atan2(angle.x as f32);
         angles.push((*angle, atan2));
     }
     // get a cycling iterator of all angles in order, starting at angle (0,-1)
@@ -66,7 +54,7 @@ pub fn part2(input: &str) -> i32 {
     let mut angles = angles
         .into_iter()
         .cycle()
-        .skip_while(|(angle, _)| !(angle.0 == 0 && angle.1 == -1));
+        .skip_while(|(angle, _)| *angle != Point::new(0, -1));
     // Here I'm sorting the Vecs so that the stations are actually in
     // order from furthest to closest. This is likely not necessary
     // for a lot of problem instances (including mine) as there are
@@ -79,7 +67,7 @@ pub fn part2(input: &str) -> i32 {
     // of doing costly front removals or switching to VecDeques
     // switching to vecdeques for quick head removal
     for visible in visibility.values_mut() {
-        visible.sort_by_key(|ast| -((station.x - ast.x).abs() + (station.y - ast.y).abs()));
+        visible.sort_by_key(|ast| -station.manhattan(*ast));
     }
     let mut destroyed_count = 0;
     while destroyed_count < 199 {
@@ -97,3 +85,5 @@ pub fn part2(input: &str) -> i32 {
     let result = &visibility[&final_angle][0];
     return 100 * result.x + result.y;
 }
+
+impl_solution!(Day10, 10);
@@ -1,67 +1,167 @@
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
 
 use crate::intcode::IntcodeMachine;
-use crate::util::Direction;
+use crate::util::{ocr, pnm, Direction, Matrix, Point, Turn};
 
-fn paint(mut program: IntcodeMachine, start_tile: i64) -> HashMap<(i32, i32), i64> {
-    let mut pos = (0, 0);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    White,
+}
+
+impl From<i64> for Color {
+    fn from(code: i64) -> Self {
+        match code {
+            0 => Color::Black,
+            1 => Color::White,
+            _ => panic!("Invalid color code {}", code),
+        }
+    }
+}
+
+impl Color {
+    fn code(self) -> i64 {
+        match self {
+            Color::Black => 0,
+            Color::White => 1,
+        }
+    }
+}
+
+/// The hull panels painted by the robot. Panels that were never
+/// painted are black.
+#[derive(Debug, Clone, Default)]
+pub struct Canvas {
+    panels: HashMap<Point, Color>,
+}
+
+impl Canvas {
+    pub fn get(&self, pos: Point) -> Color {
+        *self.panels.get(&pos).unwrap_or(&Color::Black)
+    }
+
+    pub fn paint(&mut self, pos: Point, color: Color) {
+        self.panels.insert(pos, color);
+    }
+
+    /// Number of panels painted at least once, whatever their color.
+    pub fn painted(&self) -> usize {
+        self.panels.len()
+    }
+
+    /// Smallest and largest coordinates of the panels painted `color`,
+    /// or `None` if there are none.
+    pub fn bounds(&self, color: Color) -> Option<(Point, Point)> {
+        let (xs, ys): (Vec<_>, Vec<_>) = self
+            .panels
+            .iter()
+            .filter(|(_, &c)| c == color)
+            .map(|(pos, _)| (pos.x, pos.y))
+            .unzip();
+        let (min_x, max_x) = xs.into_iter().minmax().into_option()?;
+        let (min_y, max_y) = ys.into_iter().minmax().into_option()?;
+        Some((Point::new(min_x, min_y), Point::new(max_x, max_y)))
+    }
+
+    /// The smallest area holding every white panel, with `true` for
+    /// white. This is what the registration identifier is read from.
+    /// If nothing is white the matrix has no rows.
+    pub fn to_matrix(&self) -> Matrix<bool> {
+        let (min, max) = match self.bounds(Color::White) {
+            Some(bounds) => bounds,
+            None => return Matrix::wrap(Vec::new(), 1),
+        };
+        let size = max - min;
+        let (width, height) = (size.x as usize + 1, size.y as usize + 1);
+        Matrix::from_fn(width, height, |x, y| {
+            self.get(min + Point::new(x as i32, y as i32)) == Color::White
+        })
+    }
+
+    /// Draws the white panels as `#` over a background of `.`.
+    pub fn render(&self) -> String {
+        let mut s = String::new();
+        for row in self.to_matrix().rows() {
+            s.extend(row.iter().map(|&w| if w { '#' } else { '.' }));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Writes the area returned by `to_matrix` as a PBM image, with
+    /// each panel taking up a `scale`x`scale` square.
+    pub fn write_pbm<W: Write>(&self, w: &mut W, scale: usize) -> io::Result<()> {
+        let (width, height, pixels) = self.scaled(scale);
+        pnm::write_pbm(w, width, height, pixels.map(|white| !white))
+    }
+
+    /// Same as `write_pbm`, but as a grayscale PGM image.
+    pub fn write_pgm<W: Write>(&self, w: &mut W, scale: usize) -> io::Result<()> {
+        let (width, height, pixels) = self.scaled(scale);
+        pnm::write_pgm(
+            w,
+            width,
+            height,
+            pixels.map(|white| if white { 255 } else { 0 }),
+        )
+    }
+
+    fn scaled(&self, scale: usize) -> (usize, usize, impl Iterator<Item = bool>) {
+        let matrix = self.to_matrix();
+        let (width, height) = (matrix.width() * scale, matrix.height() * scale);
+        let pixels = (0..height)
+            .flat_map(move |py| (0..width).map(move |px| (px, py)))
+            .map(move |(px, py)| matrix[(px / scale, py / scale)]);
+        (width, height, pixels)
+    }
+}
+
+fn paint(mut program: IntcodeMachine, start_color: Color) -> Canvas {
+    let mut pos = Point::origin();
     let mut facing_dir = Direction::Up;
-    let mut painted_tiles: HashMap<(i32, i32), i64> = HashMap::new();
+    let mut canvas = Canvas::default();
     let mut out_buffer = Vec::with_capacity(2);
-    let mut input = Some(start_tile);
+    let mut input = Some(start_color.code());
     while !program.is_stopped() {
         program.run_while_input(&mut input, &mut out_buffer);
-        for (&paint, &turn) in out_buffer.iter().tuples() {
-            painted_tiles.insert(pos, paint);
-            if turn == 0 {
-                facing_dir.turn_left();
-            } else {
-                facing_dir.turn_right();
-            };
-            let mov = facing_dir.tuple();
-            pos = (pos.0 + mov.0, pos.1 + mov.1);
+        for (&color, &turn) in out_buffer.iter().tuples() {
+            canvas.paint(pos, color.into());
+            facing_dir.turn(if turn == 0 { Turn::Left } else { Turn::Right });
+            pos += facing_dir;
         }
         out_buffer.clear();
-        input = Some(*painted_tiles.get(&pos).unwrap_or(&0));
+        input = Some(canvas.get(pos).code());
     }
-    painted_tiles
+    canvas
 }
 
 pub fn part1(input: &str) -> usize {
     let program = IntcodeMachine::from_str(input);
-    let painted_tiles = paint(program, 0);
-    return painted_tiles.len();
+    paint(program, Color::Black).painted()
 }
 
-pub fn part2(input: &str) {
+pub fn part2(input: &str) -> String {
     let program = IntcodeMachine::from_str(input);
-    let painted_tiles = paint(program, 1);
-
-    let mut sorted = painted_tiles
-        .keys()
-        .sorted_by(|a, b| {
-            use std::cmp::Ordering::*;
-            match (a.0.cmp(&b.0), a.1.cmp(&b.1)) {
-                (x, Equal) => x,
-                (_, y) => y,
-            }
-        })
-        .peekable();
-
-    let leftmost = *sorted.peek().unwrap();
-    for (_, line) in &sorted.group_by(|k| k.1) {
-        let mut x = leftmost.0;
-        for p in line {
-            while x <= p.0 {
-                match painted_tiles.get(p).unwrap_or(&0) {
-                    0 => print!("░"),
-                    1 => print!("▓"),
-                    _ => panic!(),
-                }
-                x += 1;
-            }
-        }
-        println!();
+    let canvas = paint(program, Color::White);
+    ocr::recognize(&canvas.to_matrix())
+        .unwrap_or_else(|e| panic!("Can't read the identifier: {}\n{}", e, canvas.render()))
+}
+
+impl_solution!(Day11, 11);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/11-1.txt");
+
+    #[test]
+    fn test_answers() {
+        assert_eq!(2720, part1(INPUT));
+        assert_eq!("JZPJRAGJ", part2(INPUT));
     }
 }
@@ -1,88 +1,108 @@
 use itertools::Itertools;
 
-use crate::util::lcm;
+use crate::util::{num, Point3};
 
-fn axis_change(a: i32, b: i32) -> (i32, i32) {
-    use std::cmp::Ordering::*;
-    match a.cmp(&b) {
-        Less => (1, -1),
-        Greater => (-1, 1),
-        Equal => (0, 0),
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Moon {
+    pos: Point3,
+    vel: Point3,
 }
 
-fn get_position_axes(input: &str) -> (Vec<i32>, Vec<i32>, Vec<i32>) {
-    let mut x_positions = Vec::with_capacity(4);
-    let mut y_positions = Vec::with_capacity(4);
-    let mut z_positions = Vec::with_capacity(4);
-
-    for l in input.lines() {
-        let mut l = l
-            .trim_matches(|c| c == '<' || c == '>')
-            .split(",")
-            .map(|s| s.trim()[2..].parse().unwrap());
-        x_positions.push(l.next().unwrap());
-        y_positions.push(l.next().unwrap());
-        z_positions.push(l.next().unwrap());
+impl Moon {
+    fn energy(&self) -> i32 {
+        let origin = Point3::origin();
+        self.pos.manhattan(origin) * self.vel.manhattan(origin)
     }
-    (x_positions, y_positions, z_positions)
 }
 
-fn sim_step(positions: &mut [i32], velocities: &mut [i32]) {
-    for (a, b) in (0..positions.len()).tuple_combinations() {
-        let (change_a, change_b) = axis_change(positions[a], positions[b]);
-        velocities[a] += change_a;
-        velocities[b] += change_b;
-    }
+fn parse_moons(input: &str) -> Vec<Moon> {
+    input
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            let (x, y, z) = l
+                .trim()
+                .trim_matches(|c| c == '<' || c == '>')
+                .split(',')
+                .map(|s| s.trim()[2..].parse().unwrap())
+                .collect_tuple()
+                .expect("Moons need three coordinates");
+            Moon {
+                pos: Point3::new(x, y, z),
+                vel: Point3::origin(),
+            }
+        })
+        .collect()
+}
 
-    for p in 0..positions.len() {
-        positions[p] += velocities[p]
+fn step(moons: &mut [Moon]) {
+    for (a, b) in (0..moons.len()).tuple_combinations() {
+        let pull = (moons[b].pos - moons[a].pos).signum();
+        moons[a].vel += pull;
+        moons[b].vel -= pull;
+    }
+    for moon in moons.iter_mut() {
+        moon.pos += moon.vel;
     }
 }
 
 pub fn part1(input: &str) -> i32 {
-    let (mut x_positions, mut y_positions, mut z_positions) = get_position_axes(input);
-    let num_particles = x_positions.len();
-    let mut x_velocities = vec![0; num_particles];
-    let mut y_velocities = vec![0; num_particles];
-    let mut z_velocities = vec![0; num_particles];
-
-    let timesteps = 1000;
-    for _ in 0..timesteps {
-        sim_step(&mut x_positions, &mut x_velocities);
-        sim_step(&mut y_positions, &mut y_velocities);
-        sim_step(&mut z_positions, &mut z_velocities);
+    let mut moons = parse_moons(input);
+    for _ in 0..1000 {
+        step(&mut moons);
     }
-    (0..num_particles)
-        .map(|i| {
-            let pot = x_positions[i].abs() + y_positions[i].abs() + z_positions[i];
-            let kin = x_velocities[i].abs() + y_velocities[i].abs() + z_velocities[i].abs();
-            pot * kin
-        })
-        .sum()
+    moons.iter().map(Moon::energy).sum()
 }
 
 pub fn part2(input: &str) -> i64 {
-    let (x_positions, y_positions, z_positions) = get_position_axes(input);
+    // the axes don't affect each other, so each one repeats on its
+    // own and the whole system repeats once all of them line up
+    let start = parse_moons(input);
+    let axes: [fn(&Point3) -> i32; 3] = [|p| p.x, |p| p.y, |p| p.z];
+    let mut repeats = [None; 3];
+    let mut moons = start.clone();
+    let mut steps = 0;
+    while repeats.iter().any(Option::is_none) {
+        step(&mut moons);
+        steps += 1;
+        for (repeat, axis) in repeats.iter_mut().zip(axes.iter()) {
+            // an axis is back where it started once every moon's
+            // position and velocity along it are
+            let back = |(moon, first): (&Moon, &Moon)| {
+                axis(&moon.pos) == axis(&first.pos) && axis(&moon.vel) == axis(&first.vel)
+            };
+            if repeat.is_none() && moons.iter().zip(start.iter()).all(back) {
+                *repeat = Some(steps);
+            }
+        }
+    }
+    num::lcm_all(repeats.iter().flatten().copied()).expect("Cycle length overflows")
+}
 
-    let x_repeat = find_axis_repeat(x_positions);
-    let y_repeat = find_axis_repeat(y_positions);
-    let z_repeat = find_axis_repeat(z_positions);
+impl_solution!(Day12, 12);
 
-    lcm(lcm(x_repeat, y_repeat), z_repeat)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn find_axis_repeat(mut positions: Vec<i32>) -> i64 {
-    let start = positions.clone();
-    let mut velocities = vec![0; positions.len()];
-    let mut step = 0;
+    const INPUT: &str = include_str!("../../input/12-1.txt");
 
-    loop {
-        step += 1;
-        sim_step(&mut positions, &mut velocities);
-        if velocities.iter().all(|v| *v == 0) && (start == positions) {
-            println!("--{:?} {:?}", &positions, &velocities);
-            return dbg!(step);
+    #[test]
+    fn test_examples() {
+        let first = "<x=-1, y=0, z=2>\n<x=2, y=-10, z=-7>\n<x=4, y=-8, z=8>\n<x=3, y=5, z=-1>";
+        let mut moons = parse_moons(first);
+        for _ in 0..10 {
+            step(&mut moons);
         }
+        assert_eq!(179, moons.iter().map(Moon::energy).sum::<i32>());
+        assert_eq!(2772, part2(first));
+        let second = "<x=-8, y=-10, z=0>\n<x=5, y=5, z=10>\n<x=2, y=-7, z=3>\n<x=9, y=-8, z=-3>";
+        assert_eq!(4_686_774_924, part2(second));
+    }
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(12082, part1(INPUT));
+        assert_eq!(295_693_702_908_636, part2(INPUT));
     }
 }
@@ -1,66 +1,438 @@
-use itertools::Itertools;
+//! Day 13: Care Package
+//!
+//! # Problem Description
+//!
+//! The Intcode program is an arcade cabinet running a breakout
+//! clone. It outputs (x, y, tile) triples to draw the screen, with
+//! `(-1, 0, score)` updating the score display instead. Part 1 asks
+//! how many blocks are on screen at the start, part 2 for the score
+//! after breaking all of them, which means actually playing the game
+//! with the joystick.
+//!
+//! # Implementation Details
+//!
+//! [`Arcade`] keeps a [`Screen`] up to date from the program's output
+//! and asks a [`Joystick`] which way to tilt whenever the game wants
+//! input. [`BallTracker`] wins by always moving the paddle towards the
+//! ball. Frames can be captured as they're drawn through a
+//! [`FrameCapture`], either to a terminal or to PPM images.
 
-use crate::intcode;
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufWriter, ErrorKind, Write},
+    path::PathBuf,
+};
 
-pub fn part1(input: &str) -> usize {
-    let mut output: Vec<i64> = Vec::new();
-    intcode::run_from_str(input, &mut std::io::empty(), &mut output);
-    output
-        .into_iter()
-        .tuples()
-        .filter(|(_, _, t)| *t == 2)
-        .count()
-}
-
-/// Modifies the game source code so that every tile on the
-/// same height as the paddle is also a paddle
-fn hack(mut source: Vec<i64>) -> Vec<i64> {
-    const EMPTY: i64 = 0;
-    const WALL: i64 = 1;
-    const PADDLE: i64 = 3;
-    // the tiles are just explicitly laid out in the code without any
-    // form of encryption, so we just need to find the paddle and fill
-    // all tiles up until the walls at the same height
-    let mut paddle_found = false;
-    let mut prev_wall = 0;
-    let mut after_wall = 0;
-    for i in 0..source.len() {
-        // paddle starts surrounded by EMPTY. This check is necessary to not confuse
-        // the tile section of the code with any other random `3`
-        if source[i] == PADDLE && source[i - 1] == EMPTY && source[i + 1] == EMPTY {
-            paddle_found = true;
+use crate::{
+    intcode::{IntcodeMachine, RunResult},
+    util::{pnm, Point},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    fn from_id(id: i64) -> Option<Self> {
+        match id {
+            0 => Some(Tile::Empty),
+            1 => Some(Tile::Wall),
+            2 => Some(Tile::Block),
+            3 => Some(Tile::Paddle),
+            4 => Some(Tile::Ball),
+            _ => None,
+        }
+    }
+}
+
+impl Tile {
+    fn to_char(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '█',
+            Tile::Block => '#',
+            Tile::Paddle => '=',
+            Tile::Ball => 'o',
+        }
+    }
+
+    fn ansi_color(self) -> &'static str {
+        match self {
+            Tile::Empty => "\x1b[0m",
+            Tile::Wall => "\x1b[37m",
+            Tile::Block => "\x1b[34m",
+            Tile::Paddle => "\x1b[33m",
+            Tile::Ball => "\x1b[31m",
+        }
+    }
+
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Tile::Empty => [0, 0, 0],
+            Tile::Wall => [128, 128, 128],
+            Tile::Block => [64, 96, 224],
+            Tile::Paddle => [240, 240, 240],
+            Tile::Ball => [224, 48, 48],
+        }
+    }
+}
+
+/// Something the game tried to draw that doesn't fit on the screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrawError {
+    /// A tile left of or above the screen, other than the score.
+    OffScreen(Point<i64>),
+    UnknownTile(i64),
+}
+
+impl fmt::Display for DrawError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawError::OffScreen(pos) => write!(f, "tile drawn off screen at {}", pos),
+            DrawError::UnknownTile(id) => write!(f, "unknown tile id {}", id),
         }
-        if source[i] == WALL {
-            prev_wall = after_wall;
-            after_wall = i;
-            if paddle_found {
-                // we have seen the paddle as well as the walls before and after it
-                break;
+    }
+}
+
+impl std::error::Error for DrawError {}
+
+/// The arcade's screen, as drawn by the game so far.
+#[derive(Debug, Clone, Default)]
+pub struct Screen {
+    tiles: HashMap<Point<i64>, Tile>,
+    width: usize,
+    height: usize,
+    score: i64,
+    ball: Option<Point<i64>>,
+    paddle: Option<Point<i64>>,
+}
+
+impl Screen {
+    /// Draws the (x, y, tile) triples output by the game, stopping at
+    /// the first one that can't be drawn.
+    pub fn draw(&mut self, output: &[i64]) -> Result<(), DrawError> {
+        for triple in output.chunks_exact(3) {
+            let (pos, value) = (Point::new(triple[0], triple[1]), triple[2]);
+            if pos == Point::new(-1, 0) {
+                self.score = value;
+                continue;
+            }
+            if pos.x < 0 || pos.y < 0 {
+                return Err(DrawError::OffScreen(pos));
             }
+            let tile = Tile::from_id(value).ok_or(DrawError::UnknownTile(value))?;
+            match tile {
+                Tile::Ball => self.ball = Some(pos),
+                Tile::Paddle => self.paddle = Some(pos),
+                _ => (),
+            }
+            self.width = self.width.max(pos.x as usize + 1);
+            self.height = self.height.max(pos.y as usize + 1);
+            self.tiles.insert(pos, tile);
         }
+        Ok(())
     }
-    // if the game code is valid, a paddle will have been found by this point
-    assert!(paddle_found);
-    for i in prev_wall+1..=after_wall-1 {
-        source[i] = PADDLE;
+
+    pub fn tile(&self, pos: Point<i64>) -> Tile {
+        *self.tiles.get(&pos).unwrap_or(&Tile::Empty)
     }
-    source
+
+    pub fn count(&self, tile: Tile) -> usize {
+        self.tiles.values().filter(|t| **t == tile).count()
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    pub fn ball(&self) -> Option<Point<i64>> {
+        self.ball
+    }
+
+    pub fn paddle(&self) -> Option<Point<i64>> {
+        self.paddle
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Renders the screen with ANSI colors, followed by the score.
+    pub fn render_ansi(&self) -> String {
+        let mut s = String::new();
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                let tile = self.tile(Point::new(x, y));
+                s.push_str(tile.ansi_color());
+                s.push(tile.to_char());
+            }
+            s.push_str("\x1b[0m\n");
+        }
+        s.push_str(&format!("Score: {}\n", self.score));
+        s
+    }
+
+    /// Writes the screen as a PPM image, with each tile taking up a
+    /// `scale`x`scale` square.
+    pub fn write_ppm<W: Write>(&self, w: &mut W, scale: usize) -> io::Result<()> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let pixels = (0..height).flat_map(|py| {
+            (0..width).map(move |px| {
+                self.tile(Point::new((px / scale) as i64, (py / scale) as i64))
+                    .rgb()
+            })
+        });
+        pnm::write_ppm(w, width, height, pixels)
+    }
+}
+
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height as i64 {
+            let line: String = (0..self.width as i64)
+                .map(|x| self.tile(Point::new(x, y)).to_char())
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        write!(f, "Score: {}", self.score)
+    }
+}
+
+/// Decides which way to tilt the joystick whenever the game asks for
+/// input: -1 for left, 0 for neutral and 1 for right.
+pub trait Joystick {
+    fn tilt(&mut self, screen: &Screen) -> i64;
+}
+
+/// Never touches the joystick.
+pub struct Neutral;
+
+impl Joystick for Neutral {
+    fn tilt(&mut self, _: &Screen) -> i64 {
+        0
+    }
+}
+
+/// Keeps the paddle under the ball by always moving towards it.
+pub struct BallTracker;
+
+impl Joystick for BallTracker {
+    fn tilt(&mut self, screen: &Screen) -> i64 {
+        match (screen.ball(), screen.paddle()) {
+            (Some(ball), Some(paddle)) => (ball.x - paddle.x).signum(),
+            _ => 0,
+        }
+    }
+}
+
+/// Receives the screen every time a frame is finished, that is, each
+/// time the game stops to read the joystick and once more at the end.
+pub trait FrameCapture {
+    fn capture(&mut self, screen: &Screen) -> io::Result<()>;
+}
+
+/// Redraws every frame over the previous one on an ANSI terminal.
+pub struct AnsiTerminal<W: Write> {
+    out: W,
+}
+
+impl<W: Write> AnsiTerminal<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+impl<W: Write> FrameCapture for AnsiTerminal<W> {
+    fn capture(&mut self, screen: &Screen) -> io::Result<()> {
+        // move cursor to the top left, then clear the screen
+        write!(self.out, "\x1b[H\x1b[2J{}", screen.render_ansi())?;
+        self.out.flush()
+    }
+}
+
+/// Saves every frame as a numbered PPM image in a directory.
+pub struct PpmFrames {
+    dir: PathBuf,
+    scale: usize,
+    next: usize,
+}
+
+impl PpmFrames {
+    pub fn new(dir: impl Into<PathBuf>, scale: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            scale,
+            next: 0,
+        }
+    }
+}
+
+impl FrameCapture for PpmFrames {
+    fn capture(&mut self, screen: &Screen) -> io::Result<()> {
+        let path = self.dir.join(format!("frame{:05}.ppm", self.next));
+        let mut file = BufWriter::new(File::create(path)?);
+        screen.write_ppm(&mut file, self.scale)?;
+        self.next += 1;
+        Ok(())
+    }
+}
+
+pub struct Arcade {
+    program: IntcodeMachine,
+    screen: Screen,
+}
+
+impl Arcade {
+    pub fn new(source_code: &str) -> Self {
+        Self {
+            program: IntcodeMachine::from_str(source_code),
+            screen: Screen::default(),
+        }
+    }
+
+    /// Creates an arcade with `quarters` already inserted, which is
+    /// needed to actually play the game.
+    pub fn with_quarters(source_code: &str, quarters: i64) -> Self {
+        let mut arcade = Self::new(source_code);
+        // memory address 0 holds the number of quarters inserted
+        arcade.program.set(0, quarters);
+        arcade
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Runs the game until it's over, returning the final score.
+    ///
+    /// # Panics
+    ///
+    /// If the game draws something that doesn't fit on the screen.
+    pub fn play(&mut self, joystick: &mut impl Joystick) -> i64 {
+        self.play_captured(joystick, &mut NoCapture)
+            .unwrap_or_else(|e| panic!("Invalid game: {}", e))
+    }
+
+    /// Same as `play`, but passing every frame to `capture`. Anything
+    /// the game can't draw is reported as an `InvalidData` error.
+    pub fn play_captured(
+        &mut self,
+        joystick: &mut impl Joystick,
+        capture: &mut impl FrameCapture,
+    ) -> io::Result<i64> {
+        let mut input = None;
+        let mut out_buffer = Vec::new();
+        loop {
+            let result = self.program.run_while_input(&mut input, &mut out_buffer);
+            // only complete triples can be drawn, any leftover is kept
+            // until the rest of it comes
+            let complete = out_buffer.len() / 3 * 3;
+            self.screen
+                .draw(&out_buffer[..complete])
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+            out_buffer.drain(..complete);
+            capture.capture(&self.screen)?;
+            if let RunResult::Stop = result {
+                return Ok(self.screen.score());
+            }
+            input = Some(joystick.tilt(&self.screen));
+        }
+    }
+}
+
+struct NoCapture;
+
+impl FrameCapture for NoCapture {
+    fn capture(&mut self, _: &Screen) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    let mut arcade = Arcade::new(input);
+    arcade.play(&mut Neutral);
+    arcade.screen().count(Tile::Block)
 }
 
 pub fn part2(input: &str) -> i64 {
-    let mut source_code: Vec<_> = input
-        .trim()
-        .split(",")
-        .map(|i| i.parse().unwrap())
-        .collect();
-    source_code[0] = 2;
-    let mut game = intcode::IntcodeMachine::copy_program(&hack(source_code));
-    let mut out_buffer: Vec<i64> = Vec::new();
-    let mut game_input = std::iter::repeat(0);
-    while !game.is_stopped() {
-        out_buffer.clear();
-        game.run_while_input(&mut game_input, &mut out_buffer);        
-    }
-    // score should be the last number outputted
-    return *out_buffer.last().unwrap()
+    let mut arcade = Arcade::with_quarters(input, 2);
+    arcade.play(&mut BallTracker)
+}
+
+impl_solution!(Day13, 13);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/13-1.txt");
+
+    #[test]
+    fn test_draw() {
+        let mut screen = Screen::default();
+        #[rustfmt::skip]
+        screen.draw(&[
+            0, 0, 1, 1, 0, 1, 2, 0, 1,
+            1, 1, 2, 2, 1, 2,
+            -1, 0, 12,
+            0, 2, 4, 1, 3, 3,
+        ]).unwrap();
+        assert_eq!((3, 4), (screen.width(), screen.height()));
+        assert_eq!(2, screen.count(Tile::Block));
+        assert_eq!(Some(Point::new(0, 2)), screen.ball());
+        assert_eq!(Some(Point::new(1, 3)), screen.paddle());
+        assert_eq!("███\n ##\no  \n = \nScore: 12", screen.to_string());
+
+        // redrawing replaces tiles and the score
+        screen
+            .draw(&[1, 1, 0, 0, 2, 0, 1, 2, 4, -1, 0, 15])
+            .unwrap();
+        assert_eq!(1, screen.count(Tile::Block));
+        assert_eq!(Some(Point::new(1, 2)), screen.ball());
+        assert_eq!(15, screen.score());
+    }
+
+    #[test]
+    fn test_draw_errors() {
+        let mut screen = Screen::default();
+        assert_eq!(
+            Err(DrawError::OffScreen(Point::new(-2, 0))),
+            screen.draw(&[-2, 0, 1])
+        );
+        assert_eq!(
+            Err(DrawError::OffScreen(Point::new(-1, 1))),
+            screen.draw(&[-1, 1, 1])
+        );
+        assert_eq!(Err(DrawError::UnknownTile(5)), screen.draw(&[0, 0, 5]));
+        assert_eq!((0, 0), (screen.width(), screen.height()));
+    }
+
+    #[test]
+    fn test_ball_tracker() {
+        let mut screen = Screen::default();
+        assert_eq!(0, BallTracker.tilt(&screen));
+        screen.draw(&[4, 5, 3, 6, 2, 4]).unwrap();
+        assert_eq!(1, BallTracker.tilt(&screen));
+        screen.draw(&[1, 3, 4]).unwrap();
+        assert_eq!(-1, BallTracker.tilt(&screen));
+        screen.draw(&[4, 4, 4]).unwrap();
+        assert_eq!(0, BallTracker.tilt(&screen));
+    }
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(462, part1(INPUT));
+        assert_eq!(23981, part2(INPUT));
+    }
 }
@@ -1,30 +1,125 @@
 use itertools::Itertools;
-use petgraph::{algo::toposort, prelude::*};
+use petgraph::{algo::toposort, dot::Dot, prelude::*};
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
 
-fn parse_chemical(c: &str) -> (u64, &str) {
-    let (n, chem) = c.split(" ").collect_tuple().unwrap();
-    (n.parse().unwrap(), chem)
+const TRILLION: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReactionError {
+    /// The line isn't in the form `1 A, 2 B => 3 C`.
+    Malformed { line: usize, text: String },
+    /// Quantities have to be positive whole numbers.
+    InvalidQuantity { line: usize, text: String },
+    /// Every chemical can only be made by one reaction.
+    DuplicateReaction { line: usize, chemical: String },
+    /// A chemical other than ORE that no reaction makes.
+    UnknownChemical(String),
+    /// ORE can only be mined, not made.
+    MakesOre { line: usize },
+    /// A chemical that's needed, maybe indirectly, to make itself.
+    Cycle(String),
+}
+
+impl fmt::Display for ReactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ReactionError::*;
+        match self {
+            Malformed { line, text } => write!(f, "line {}: can't read reaction `{}`", line, text),
+            InvalidQuantity { line, text } => {
+                write!(f, "line {}: `{}` is not a valid quantity", line, text)
+            }
+            DuplicateReaction { line, chemical } => {
+                write!(
+                    f,
+                    "line {}: there's already a reaction for {}",
+                    line, chemical
+                )
+            }
+            UnknownChemical(c) => write!(f, "no reaction makes {}", c),
+            MakesOre { line } => write!(f, "line {}: ORE can't be made by a reaction", line),
+            Cycle(c) => write!(f, "{} is needed to make itself", c),
+        }
+    }
 }
 
+impl std::error::Error for ReactionError {}
+
+/// A chemical, along with how much of it one reaction makes. That's 0
+/// for ORE, which isn't made by any.
+#[derive(Debug, Clone, Copy)]
+struct Chemical<'a> {
+    name: &'a str,
+    makes: u64,
+}
+
+impl fmt::Display for Chemical<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.makes == 0 {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} {}", self.makes, self.name)
+        }
+    }
+}
+
+/// The weight of a A->B edge indicates how much B one reaction making
+/// A consumes.
+#[derive(Debug, Clone, Copy)]
 struct ReactionEdge {
-    produces: u64,
     consumes: u64,
 }
 
-impl ReactionEdge {
-    fn new(produces: u64, consumes: u64) -> Self {
-        Self { produces, consumes }
+impl fmt::Display for ReactionEdge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.consumes)
     }
 }
 
-struct Reactions<'a> {
-    // Holds the actual dependencies between each node.The data
-    // associated with a node is used to calculate needed quantities
-    // and starts at 0. The weight of a A->B edge indicates how much
-    // B is needed to create a number of A
-    deps: DiGraph<u64, ReactionEdge>,
+/// Everything that goes into making some amount of FUEL.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Plan<'a> {
+    /// How many times the reaction making each chemical runs.
+    pub runs: BTreeMap<&'a str, u64>,
+    /// How much of each chemical gets used up, including ORE and the
+    /// FUEL that was asked for.
+    pub consumed: BTreeMap<&'a str, u64>,
+    /// How much of each chemical is left over, because reactions
+    /// only make it in batches.
+    pub surplus: BTreeMap<&'a str, u64>,
+}
+
+impl Plan<'_> {
+    pub fn ore(&self) -> u64 {
+        self.consumed.get("ORE").copied().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Plan<'_> {
+    /// Writes the bill of materials, one chemical per line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (chem, consumed) in self.consumed.iter() {
+            write!(f, "{:>12} {}", consumed, chem)?;
+            if let Some(&runs) = self.runs.get(chem) {
+                let plural = if runs == 1 { "" } else { "s" };
+                write!(f, " in {} reaction{}", runs, plural)?;
+            }
+            match self.surplus.get(chem) {
+                Some(&surplus) if surplus > 0 => writeln!(f, ", {} left over", surplus)?,
+                _ => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Reactions<'a> {
+    // Holds the actual dependencies between each node, from each
+    // chemical to the ones needed to make it
+    deps: DiGraph<Chemical<'a>, ReactionEdge>,
     // this is a pre-computed topological sort so there's no need
     // to find it every time
     sorted: Vec<NodeIndex>,
@@ -32,77 +127,302 @@ struct Reactions<'a> {
     chemicals: HashMap<&'a str, NodeIndex>,
 }
 
+fn parse_chemical(c: &str, line: usize) -> Result<(u64, &str), ReactionError> {
+    let (n, chem) =
+        c.split_whitespace()
+            .collect_tuple()
+            .ok_or_else(|| ReactionError::Malformed {
+                line,
+                text: c.to_string(),
+            })?;
+    match n.parse() {
+        Ok(n) if n > 0 => Ok((n, chem)),
+        _ => Err(ReactionError::InvalidQuantity {
+            line,
+            text: n.to_string(),
+        }),
+    }
+}
+
 impl<'a> Reactions<'a> {
-    fn from_input(input: &'a str) -> Self {
+    pub fn parse(input: &'a str) -> Result<Self, ReactionError> {
         let mut deps = DiGraph::new();
         let mut chemicals = HashMap::new();
-        for line in input.lines() {
-            let (consumes, produces) = line.split("=>").map(|x| x.trim()).collect_tuple().unwrap();
-            let produces = parse_chemical(produces);
-            let consumes = consumes.split(",").map(|x| parse_chemical(x.trim()));
-            let from_node = *chemicals
-                .entry(produces.1)
-                .or_insert_with(|| deps.add_node(0));
-            for c in consumes {
-                let to_node = *chemicals.entry(c.1).or_insert_with(|| deps.add_node(0));
-                deps.add_edge(from_node, to_node, ReactionEdge::new(produces.0, c.0));
+        let mut node = |deps: &mut DiGraph<_, _>, name| {
+            *chemicals
+                .entry(name)
+                .or_insert_with(|| deps.add_node(Chemical { name, makes: 0 }))
+        };
+        for (i, line) in input
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+        {
+            let line_num = i + 1;
+            let (consumes, produces) = line
+                .split("=>")
+                .map(|x| x.trim())
+                .collect_tuple()
+                .ok_or_else(|| ReactionError::Malformed {
+                    line: line_num,
+                    text: line.to_string(),
+                })?;
+            let (makes, name) = parse_chemical(produces, line_num)?;
+            if name == "ORE" {
+                return Err(ReactionError::MakesOre { line: line_num });
+            }
+            let from_node = node(&mut deps, name);
+            if deps[from_node].makes != 0 {
+                return Err(ReactionError::DuplicateReaction {
+                    line: line_num,
+                    chemical: name.to_string(),
+                });
             }
+            deps[from_node].makes = makes;
+            for c in consumes.split(',') {
+                let (consumes, name) = parse_chemical(c, line_num)?;
+                let to_node = node(&mut deps, name);
+                deps.add_edge(from_node, to_node, ReactionEdge { consumes });
+            }
+        }
+
+        for name in ["FUEL", "ORE"].iter() {
+            node(&mut deps, name);
+        }
+        if let Some(chem) = deps
+            .node_indices()
+            .map(|n| deps[n])
+            .find(|c| c.makes == 0 && c.name != "ORE")
+        {
+            return Err(ReactionError::UnknownChemical(chem.name.to_string()));
         }
-        let sorted = toposort(&deps, None).unwrap();
-        Reactions {
+        let sorted = toposort(&deps, None)
+            .map_err(|c| ReactionError::Cycle(deps[c.node_id()].name.to_string()))?;
+        Ok(Reactions {
             deps,
             sorted,
             chemicals,
-        }
+        })
     }
-    // cleans the required counts for all chemicals
-    fn clear_nodes(&mut self) {
-        for weight in self.deps.node_weights_mut() {
-            *weight = 0;
+
+    /// Works out the reactions needed to make `fuel` FUEL. Going
+    /// through the chemicals in topological order means everything
+    /// that needs one has asked for it by the time it's reached, so
+    /// each reaction is run as few times as possible, and whatever it
+    /// makes beyond that is kept as surplus. Returns `None` if any
+    /// amount involved doesn't fit in a `u64`.
+    pub fn plan(&self, fuel: u64) -> Option<Plan<'a>> {
+        let mut needed = vec![0; self.deps.node_count()];
+        needed[self.chemicals["FUEL"].index()] = fuel;
+        let mut plan = Plan::default();
+        for &chem in self.sorted.iter() {
+            let Chemical { name, makes } = self.deps[chem];
+            let chem_needed = needed[chem.index()];
+            if chem_needed == 0 {
+                continue;
+            }
+            plan.consumed.insert(name, chem_needed);
+            if makes == 0 {
+                continue;
+            }
+            let runs = chem_needed.div_ceil(makes);
+            plan.runs.insert(name, runs);
+            // runs * makes can overflow even when the amount needed fits
+            let surplus = (makes - chem_needed % makes) % makes;
+            plan.surplus.insert(name, surplus);
+            for edge in self.deps.edges(chem) {
+                let consumed = runs.checked_mul(edge.weight().consumes)?;
+                let target = &mut needed[edge.target().index()];
+                *target = target.checked_add(consumed)?;
+            }
         }
+        Some(plan)
+    }
+
+    /// The ORE needed to make `fuel` FUEL, or `None` if it doesn't fit
+    /// in a `u64`.
+    pub fn ore_for(&self, fuel: u64) -> Option<u64> {
+        self.plan(fuel).map(|plan| plan.ore())
     }
 
-    fn resources_required_for(&mut self, chem: &str, quantity: u64, resource: &str) -> u64 {
-        self.clear_nodes();
-        // set required quantity for starting chemical
-        let start = self.chemicals[chem];
-        self.deps[start] = quantity;
-        // in this problem the node we care about (FUEL) should
-        // be the first one, but let's just add this to make sure we
-        // start with it
-        let sorted = self.sorted.iter().skip_while(|n| **n != start);
-        for &chem in sorted {
-            let mut neighbors = self.deps.neighbors(chem).detach();
-            let chem_needed = self.deps[chem];
-            while let Some((edge, neighbor)) = neighbors.next(&self.deps) {
-                let (produces, consumes) = (self.deps[edge].produces, self.deps[edge].consumes);
-                let mut needed_reactions = chem_needed / produces;
-                if chem_needed % produces != 0 {
-                    // round up
-                    needed_reactions += 1;
-                }
-                self.deps[neighbor] += needed_reactions * consumes;
+    /// The most FUEL that can be made with `ore` ORE. Making more FUEL
+    /// never takes less ORE, so this can binary search for it, starting
+    /// from what it'd take if no ORE was wasted on surplus. Amounts too
+    /// big to work out are over any budget.
+    pub fn max_fuel(&self, ore: u64) -> u64 {
+        let fits = |fuel| self.ore_for(fuel).is_some_and(|needed| needed <= ore);
+        let per_fuel = match self.ore_for(1) {
+            Some(per_fuel) if per_fuel <= ore => per_fuel,
+            _ => return 0,
+        };
+        let (mut low, mut high) = (0, (ore / per_fuel.max(1)).max(1));
+        while fits(high) {
+            if high == u64::MAX {
+                return high;
             }
+            low = high;
+            high = high.saturating_mul(2);
         }
-        self.deps[self.chemicals[resource]]
+        // making `low` fits in the budget while `high` doesn't
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// The reactions as a graph in the DOT language, with an edge from
+    /// each chemical to the ones needed to make it, labeled with how
+    /// much of it a reaction needs.
+    pub fn to_dot(&self) -> String {
+        Dot::new(&self.deps).to_string()
     }
 }
 
 pub fn part1(input: &str) -> u64 {
-    let mut reactions = Reactions::from_input(input);
-    reactions.resources_required_for("FUEL", 1, "ORE")
+    let reactions = Reactions::parse(input).unwrap_or_else(|e| panic!("{}", e));
+    reactions.ore_for(1).expect("Too much ORE needed to count")
 }
 
 pub fn part2(input: &str) -> u64 {
-    let mut reactions = Reactions::from_input(input);
-    let fuel_ore_rate = reactions.resources_required_for("FUEL", 1, "ORE");
-    const TRILLION: u64 = 1_000_000_000_000;
-    let start_guess = TRILLION / fuel_ore_rate;
-    for i in start_guess.. {
-        let required = reactions.resources_required_for("FUEL", i, "ORE");
-        if required > TRILLION {
-            return i - 1;
+    let reactions = Reactions::parse(input).unwrap_or_else(|e| panic!("{}", e));
+    reactions.max_fuel(TRILLION)
+}
+
+impl_solution!(Day14, 14);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+157 ORE => 5 NZVS
+165 ORE => 6 DCFZ
+44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+179 ORE => 7 PSHF
+177 ORE => 5 HKGWZ
+7 DCFZ, 7 PSHF => 2 XJWVT
+165 ORE => 2 GPVTF
+3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT";
+
+    #[test]
+    fn test_plan() {
+        let reactions = Reactions::parse(EXAMPLE).unwrap();
+        let plan = reactions.plan(1).unwrap();
+        assert_eq!(13312, plan.ore());
+        assert_eq!(Some(&1), plan.consumed.get("FUEL"));
+        assert_eq!(Some(&1), plan.runs.get("KHKGT"));
+        assert_eq!(Some(&3), plan.surplus.get("KHKGT"));
+        assert_eq!(None, plan.runs.get("ORE"));
+        // whatever's made is either consumed or left over
+        for (chem, runs) in plan.runs.iter() {
+            let makes = reactions.deps[reactions.chemicals[chem]].makes;
+            assert_eq!(runs * makes, plan.consumed[chem] + plan.surplus[chem]);
         }
+        assert_eq!(Some(0), reactions.ore_for(0));
+        assert_eq!(82_892_753, reactions.max_fuel(TRILLION));
+        assert_eq!(0, reactions.max_fuel(13311));
+        assert_eq!(1, reactions.max_fuel(13312));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let reactions = Reactions::parse(EXAMPLE).unwrap();
+        assert_eq!(None, reactions.plan(u64::MAX));
+        assert_eq!(None, reactions.ore_for(u64::MAX / 1000));
+        let fuel = reactions.max_fuel(u64::MAX);
+        assert!(reactions.ore_for(fuel).is_some());
+        // anything that can be worked out fits in this budget
+        assert_eq!(None, reactions.ore_for(fuel + 1));
+
+        // making FUEL in big enough batches takes less ORE than FUEL
+        let cheap = Reactions::parse("1 ORE => 10 FUEL").unwrap();
+        assert_eq!(Some(u64::MAX / 10 + 1), cheap.ore_for(u64::MAX));
+        assert_eq!(u64::MAX, cheap.max_fuel(u64::MAX));
+        assert_eq!(20, cheap.max_fuel(2));
+    }
+
+    #[test]
+    fn test_validation() {
+        let err = |s| Reactions::parse(s).err().unwrap();
+        assert_eq!(
+            ReactionError::Malformed {
+                line: 2,
+                text: "2 ORE -> 1 A".to_string()
+            },
+            err("1 A => 1 FUEL\n2 ORE -> 1 A")
+        );
+        assert_eq!(
+            ReactionError::InvalidQuantity {
+                line: 1,
+                text: "0".to_string()
+            },
+            err("0 ORE => 1 FUEL")
+        );
+        assert_eq!(
+            ReactionError::DuplicateReaction {
+                line: 3,
+                chemical: "A".to_string()
+            },
+            err("1 A => 1 FUEL\n1 ORE => 1 A\n2 ORE => 3 A")
+        );
+        assert_eq!(
+            ReactionError::UnknownChemical("B".to_string()),
+            err("1 A => 1 FUEL\n1 B => 1 A")
+        );
+        assert_eq!(
+            ReactionError::UnknownChemical("FUEL".to_string()),
+            err("1 ORE => 1 A")
+        );
+        assert_eq!(
+            ReactionError::MakesOre { line: 2 },
+            err("1 ORE => 1 FUEL\n1 FUEL => 2 ORE")
+        );
+        assert!(matches!(
+            err("1 A => 1 FUEL\n1 B, 1 ORE => 1 A\n1 A => 1 B"),
+            ReactionError::Cycle(_)
+        ));
+    }
+
+    #[test]
+    fn test_output() {
+        let reactions = Reactions::parse("10 ORE => 10 A\n7 A, 1 ORE => 1 FUEL").unwrap();
+        let dot = reactions.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("[label=\"10 A\"]"));
+        assert!(dot.contains("[label=\"ORE\"]"));
+        assert!(dot.contains("[label=\"7\"]"));
+        assert_eq!(3, dot.matches("->").count());
+
+        let bill = |fuel| -> Vec<_> {
+            reactions
+                .plan(fuel)
+                .unwrap()
+                .to_string()
+                .lines()
+                .map(|l| l.trim().to_string())
+                .collect()
+        };
+        assert_eq!(
+            vec![
+                "7 A in 1 reaction, 3 left over",
+                "1 FUEL in 1 reaction",
+                "11 ORE"
+            ],
+            bill(1)
+        );
+        assert_eq!(
+            vec![
+                "14 A in 2 reactions, 6 left over",
+                "2 FUEL in 2 reactions",
+                "22 ORE"
+            ],
+            bill(2)
+        );
     }
-    unreachable!()
 }
@@ -1,183 +1,248 @@
-use itertools::iproduct;
-use petgraph::prelude::*;
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 
-use crate::{intcode::IntcodeMachine, util::Direction};
-
-fn dir_to_int(dir: &Direction) -> i64 {
-    use Direction::*;
-    match *dir {
-        Up => 1,
-        Down => 2,
-        Left => 3,
-        Right => 4,
-    }
-}
-
-fn surrounding(x: i32, y: i32) -> ((i32, i32), (i32, i32), (i32, i32), (i32, i32)) {
-    ((x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y))
-}
+use crate::{
+    intcode::IntcodeMachine,
+    util::{search, Direction, Grid, Point},
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Occupancy {
-    Unknown,
+pub enum Cell {
     Wall,
-    Empty,
+    Open,
     Oxygen,
 }
 
-impl Default for Occupancy {
-    fn default() -> Self {
-        Occupancy::Unknown
-    }
-}
-
-impl From<i64> for Occupancy {
-    fn from(i: i64) -> Occupancy {
+impl From<i64> for Cell {
+    fn from(i: i64) -> Cell {
         match i {
-            0 => Occupancy::Wall,
-            1 => Occupancy::Empty,
-            2 => Occupancy::Oxygen,
-            _ => panic!(),
+            0 => Cell::Wall,
+            1 => Cell::Open,
+            2 => Cell::Oxygen,
+            _ => panic!("Invalid droid status code {}", i),
         }
     }
 }
 
-#[derive(Clone)]
-struct Robot {
+struct Droid {
     program: IntcodeMachine,
-    pos: (i32, i32),
 }
 
-impl Robot {
-    fn step(&mut self, dir: Direction) -> ((i32, i32), Occupancy) {
-        let mut input = Some(dir_to_int(&dir));
+impl Droid {
+    /// Tries moving in the given direction, returning what was
+    /// found there. The droid only actually moves if it's not a wall.
+    fn step(&mut self, dir: Direction) -> Cell {
+        let mut input = Some(dir.intcode());
         let mut out = None;
         self.program.run_while_input(&mut input, &mut out);
-        let occ = out.unwrap().into();
-        let mov = dir.tuple();
-        let new = (self.pos.0 + mov.0, self.pos.1 + mov.1);
-        match occ {
-            Occupancy::Wall => (),
-            _ => {
-                // if not wall, move
-                self.pos = new;
-            }
-        }
-        (new, occ)
+        out.expect("Droid didn't report its status").into()
     }
 }
 
-struct RobotMemory {
-    graph: UnGraph<Occupancy, ()>,
-    idx_map: HashMap<(i32, i32), NodeIndex>,
-    oxygen_pos: Option<(i32, i32)>,
+/// The area explored by the repair droid. Only positions the droid
+/// has actually seen are stored, so there's no limit on its size or
+/// where the droid starts.
+pub struct Maze {
+    cells: HashMap<Point, Cell>,
+    start: Point,
+    oxygen: Option<Point>,
 }
 
-impl RobotMemory {
-    fn new(size: i32) -> Self {
-        let mut graph = UnGraph::new_undirected();
-        let mut map = HashMap::new();
-        for (x, y) in iproduct!(0..size, 0..size) {
-            let n = graph.add_node(Occupancy::Unknown);
-            map.insert((x, y), n);
-        }
-        // initialize the starting position as Empty
-        graph[map[&(size / 2, size / 2)]] = Occupancy::Empty;
-        RobotMemory {
-            graph,
-            idx_map: map,
-            oxygen_pos: None,
+impl Maze {
+    /// Maps every position reachable by the droid. The droid explores
+    /// depth first and backtracks by retracing its steps, so a single
+    /// copy of the program is enough.
+    pub fn explore(program: IntcodeMachine) -> Self {
+        let mut droid = Droid { program };
+        let start = Point::origin();
+        let mut maze = Maze {
+            cells: HashMap::new(),
+            start,
+            oxygen: None,
+        };
+        maze.cells.insert(start, Cell::Open);
+        let mut pos = start;
+        // moves taken to get to the current position from the start
+        let mut path = Vec::new();
+        loop {
+            let unexplored = Direction::all().find(|&dir| !maze.cells.contains_key(&(pos + dir)));
+            if let Some(dir) = unexplored {
+                let next = pos + dir;
+                let cell = droid.step(dir);
+                maze.cells.insert(next, cell);
+                if cell == Cell::Oxygen {
+                    maze.oxygen = Some(next);
+                }
+                if cell != Cell::Wall {
+                    pos = next;
+                    path.push(dir);
+                }
+            } else if let Some(dir) = path.pop() {
+                // nothing new around here, go back the way we came
+                let back = dir.opposite();
+                droid.step(back);
+                pos += back;
+            } else {
+                // back at the start with everything explored
+                return maze;
+            }
         }
     }
 
-    fn get(&self, pos: (i32, i32)) -> Occupancy {
-        self.graph[self.idx_map[&pos]]
+    pub fn get(&self, pos: Point) -> Option<Cell> {
+        self.cells.get(&pos).cloned()
     }
 
-    fn get_oxygen_node(&self) -> Option<NodeIndex> {
-        self.oxygen_pos.map(|p| self.idx_map[&p])
+    pub fn start(&self) -> Point {
+        self.start
     }
 
-    fn connect(&mut self, pos: (i32, i32), occupancy: Occupancy) {
-        if let Occupancy::Oxygen = occupancy {
-            self.oxygen_pos = Some(pos);
-        }
-        let (u, d, l, r) = surrounding(pos.0, pos.1);
-        let curr = self.idx_map[&pos];
-        self.graph[curr] = occupancy;
-        let surrounding = [
-            self.idx_map[&u],
-            self.idx_map[&d],
-            self.idx_map[&l],
-            self.idx_map[&r],
-        ];
-        let mut edges = Vec::with_capacity(4);
-        for n in surrounding.into_iter() {
-            match self.graph[*n] {
-                Occupancy::Wall | Occupancy::Unknown => continue,
-                _ => edges.push((curr, *n)),
-            }
+    pub fn oxygen(&self) -> Option<Point> {
+        self.oxygen
+    }
+
+    /// Finds the distance from `from` to every open position that can
+    /// be reached from it.
+    pub fn distances_from(&self, from: Point) -> HashMap<Point, usize> {
+        search::bfs(Some(from), |&pos| self.open_neighbors(pos)).collect()
+    }
+
+    /// Explored positions next to `pos` that aren't walls.
+    fn open_neighbors(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::all()
+            .map(move |dir| pos + dir)
+            .filter(move |&next| matches!(self.get(next), Some(Cell::Open) | Some(Cell::Oxygen)))
+    }
+
+    /// Number of moves in the shortest path between two positions.
+    pub fn shortest_path(&self, from: Point, to: Point) -> Option<usize> {
+        search::bfs(Some(from), |&pos| self.open_neighbors(pos))
+            .find(|&(pos, _)| pos == to)
+            .map(|(_, dist)| dist)
+    }
+
+    /// Minutes it takes for oxygen to spread from the oxygen system to
+    /// the whole area.
+    pub fn fill_time(&self) -> Option<usize> {
+        let oxygen = self.oxygen?;
+        search::bfs(Some(oxygen), |&pos| self.open_neighbors(pos))
+            .last()
+            .map(|(_, dist)| dist)
+    }
+
+    /// Smallest and largest coordinates seen.
+    pub fn bounds(&self) -> (Point, Point) {
+        let xs = self.cells.keys().map(|p| p.x);
+        let ys = self.cells.keys().map(|p| p.y);
+        (
+            Point::new(xs.clone().min().unwrap(), ys.clone().min().unwrap()),
+            Point::new(xs.max().unwrap(), ys.max().unwrap()),
+        )
+    }
+
+    /// The explored area as a grid just big enough to hold it, with
+    /// the smallest coordinates seen at its top left and `None` for
+    /// anything unexplored.
+    pub fn to_grid(&self) -> Grid<Option<Cell>> {
+        let (min, max) = self.bounds();
+        let size = max - min;
+        let (width, height) = (size.x as usize + 1, size.y as usize + 1);
+        let mut grid = Grid::filled(width, height, None);
+        for (&pos, &cell) in self.cells.iter() {
+            grid[pos - min] = Some(cell);
         }
-        self.graph.extend_with_edges(edges);
+        grid
     }
 
-    fn surrounding_occupancy(
-        &self,
-        pos: (i32, i32),
-    ) -> (Occupancy, Occupancy, Occupancy, Occupancy) {
-        let (u, d, l, r) = surrounding(pos.0, pos.1);
-        (self.get(u), self.get(d), self.get(l), self.get(r))
+    /// Draws the maze with `#` for walls, `.` for open space, `D` for
+    /// where the droid started, `O` for the oxygen system and a blank
+    /// for anything unexplored.
+    pub fn render(&self) -> String {
+        let (min, _) = self.bounds();
+        let mut grid = self.to_grid().map(|cell| match cell {
+            Some(Cell::Wall) => '#',
+            Some(Cell::Open) => '.',
+            Some(Cell::Oxygen) => 'O',
+            None => ' ',
+        });
+        grid[self.start - min] = 'D';
+        grid.to_string()
     }
 }
 
-fn run_robots(program: IntcodeMachine) -> RobotMemory {
-    // creating a suitably large memory -- the size of the area is unknown
-    // but it's assumed to not be incredibly large
-    let mut map_memory = RobotMemory::new(50);
-    let origin = Robot {
-        program: program.clone(),
-        pos: (25, 25),
-    };
-
-    let mut queue = VecDeque::with_capacity(4);
-    queue.push_back(origin);
-    // let mut out_buffer = None;
-    use Direction::*;
-    let dirs = [Up, Down, Left, Right];
-    while let Some(robot) = queue.pop_front() {
-        let (u, d, l, r) = map_memory.surrounding_occupancy(robot.pos);
-        for (&dir, neighbor) in dirs.iter().zip([u, d, l, r].into_iter()) {
-            // only walk into unknown territory
-            if let Occupancy::Unknown = neighbor {
-                let mut new = robot.clone();
-                let (pos, occ) = new.step(dir);
-                map_memory.connect(pos, occ);
-                match occ {
-                    Occupancy::Wall => continue,
-                    _ => (),
-                }
-                // new robot will spawn more
-                queue.push_back(new);
+pub fn part1(input: &str) -> usize {
+    let maze = Maze::explore(IntcodeMachine::from_str(input));
+    let oxygen = maze.oxygen().expect("Oxygen system not found");
+    maze.shortest_path(maze.start(), oxygen).unwrap()
+}
+
+pub fn part2(input: &str) -> usize {
+    let maze = Maze::explore(IntcodeMachine::from_str(input));
+    maze.fill_time().expect("Oxygen system not found")
+}
+
+impl_solution!(Day15, 15);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/15-1.txt");
+
+    /// The oxygen example from the puzzle, with the droid starting in
+    /// the top left corner.
+    const EXAMPLE: &str = " ##   \n#D.## \n#.#..#\n#.O.# \n ###  \n";
+
+    /// Builds a maze from its rendering, leaving blanks unexplored.
+    fn maze(map: &str) -> Maze {
+        let mut maze = Maze {
+            cells: HashMap::new(),
+            start: Point::origin(),
+            oxygen: None,
+        };
+        for (y, line) in map.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let pos = Point::new(x as i32, y as i32);
+                let cell = match c {
+                    '#' => Cell::Wall,
+                    '.' => Cell::Open,
+                    'D' => {
+                        maze.start = pos;
+                        Cell::Open
+                    }
+                    'O' => {
+                        maze.oxygen = Some(pos);
+                        Cell::Oxygen
+                    }
+                    _ => continue,
+                };
+                maze.cells.insert(pos, cell);
             }
         }
+        maze
+    }
+
+    #[test]
+    fn test_example() {
+        let example = maze(EXAMPLE);
+        assert_eq!(EXAMPLE, example.render());
+        assert_eq!(Some(4), example.fill_time());
+
+        let distances = example.distances_from(example.start());
+        assert_eq!(8, distances.len());
+        assert_eq!(Some(&3), distances.get(&Point::new(2, 3)));
+        assert_eq!(Some(&6), distances.get(&Point::new(4, 2)));
+        assert_eq!(None, distances.get(&Point::new(5, 2)));
+        let start = example.start();
+        assert_eq!(Some(3), example.shortest_path(start, Point::new(2, 3)));
+        assert_eq!(None, example.shortest_path(start, Point::new(0, 0)));
+
+        let no_oxygen = maze(&EXAMPLE.replace('O', "."));
+        assert_eq!(None, no_oxygen.fill_time());
     }
-    map_memory
-}
-pub fn part1(input: &str) -> u32 {
-    let program = IntcodeMachine::from_str(input);
-    let memory = run_robots(program);
-    let start = memory.idx_map[&(25, 25)];
-    let end = memory.get_oxygen_node().unwrap();
-    petgraph::algo::astar(&memory.graph, start, |n| n == end, |_| 1, |_| 0)
-        .unwrap()
-        .0
-}
 
-pub fn part2(input: &str) -> u32 {
-    let program = IntcodeMachine::from_str(input);
-    let memory = run_robots(program);
-    let oxygen = memory.get_oxygen_node().unwrap();
-    let dists = petgraph::algo::dijkstra(&memory.graph, oxygen, None, |_| 1);
-    *dists.values().max().unwrap()
+    #[test]
+    fn test_parts() {
+        assert_eq!(224, part1(INPUT));
+        assert_eq!(284, part2(INPUT));
+    }
 }
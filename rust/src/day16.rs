@@ -1,6 +1,9 @@
 use itertools::join;
 use std::iter::repeat;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
 /// Returns iterator for the pattern of a given digit with position
 /// `i`.  Unlike what is described in the problem, this pattern starts
 /// with the first 1, and as such is intended for use with the i-th
@@ -14,7 +17,9 @@ fn find_pattern(digit: usize) -> impl Iterator<Item = i32> {
     pattern.cycle()
 }
 
-fn fft(mut digits: Vec<i32>, phases: usize) -> Vec<i32> {
+/// Runs the phases by multiplying each digit by its pattern, which
+/// takes O(n^2) per phase. Kept to check and benchmark [`fft`] against.
+pub fn fft_naive(mut digits: Vec<i32>, phases: usize) -> Vec<i32> {
     let size = digits.len();
     let mut old_buffer = Vec::with_capacity(size);
     for _phase in 1..=phases {
@@ -32,34 +37,137 @@ fn fft(mut digits: Vec<i32>, phases: usize) -> Vec<i32> {
     digits
 }
 
-pub fn part1(input: &str) -> String {
-    let digits: Vec<i32> = input
+/// Fills `prefix` so that `prefix[j]` is the sum of the first `j`
+/// digits.
+fn prefix_sums(digits: &[i32], prefix: &mut Vec<i32>) {
+    prefix.clear();
+    prefix.push(0);
+    let mut sum = 0;
+    prefix.extend(digits.iter().map(|d| {
+        sum += d;
+        sum
+    }));
+}
+
+/// The next value of the digit at position `i` of the whole signal,
+/// given the prefix sums of the digits from `offset` on.
+///
+/// The pattern for position `i` is made of blocks of `i + 1` digits.
+/// Nothing before `i` counts, and from there on the blocks go 1, 0, -1
+/// and 0, so the digit is just the sums of every other block, added or
+/// subtracted in turn. Each takes one lookup in `prefix`, and there are
+/// about `n / (i + 1)` of them, which adds up to O(n log n) for the
+/// whole phase.
+fn next_digit(prefix: &[i32], offset: usize, i: usize) -> i32 {
+    let end = prefix.len() - 1;
+    let block = i + 1;
+    let sum = |from: usize| prefix[(from + block).min(end)] - prefix[from.min(end)];
+    let mut total = 0;
+    let mut start = i - offset;
+    while start < end {
+        total += sum(start);
+        total -= sum(start + 2 * block);
+        start += 4 * block;
+    }
+    total.abs() % 10
+}
+
+/// Runs `phases` phases over the part of a signal starting at position
+/// `offset`. Every output digit only depends on the digits at or after
+/// its own position, so that's all it takes to get them from `offset`
+/// on, and passing the whole signal with an `offset` of 0 gives the
+/// same as [`fft_naive`].
+pub fn fft(mut digits: Vec<i32>, offset: usize, phases: usize) -> Vec<i32> {
+    let mut prefix = Vec::with_capacity(digits.len() + 1);
+    for _phase in 0..phases {
+        prefix_sums(&digits, &mut prefix);
+        for (i, d) in digits.iter_mut().enumerate() {
+            *d = next_digit(&prefix, offset, offset + i);
+        }
+    }
+    digits
+}
+
+/// Same as [`fft`], but computes the digits of each phase in parallel.
+#[cfg(feature = "rayon")]
+pub fn fft_par(mut digits: Vec<i32>, offset: usize, phases: usize) -> Vec<i32> {
+    let mut prefix = Vec::with_capacity(digits.len() + 1);
+    for _phase in 0..phases {
+        prefix_sums(&digits, &mut prefix);
+        let prefix = &prefix;
+        digits
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, d)| *d = next_digit(prefix, offset, offset + i));
+    }
+    digits
+}
+
+fn parse_digits(input: &str) -> Vec<i32> {
+    input
         .trim()
         .chars()
         .map(|c| c.to_digit(10).unwrap() as i32)
-        .collect();
-    let digits = fft(digits, 100);
+        .collect()
+}
+
+/// The eight digits at `offset` after 100 phases over `digits` repeated
+/// `repeats` times.
+pub fn decode(digits: &[i32], repeats: usize, offset: usize) -> String {
+    let len = digits.len() * repeats;
+    assert!(
+        offset + 8 <= len,
+        "Message offset {} is past the end of the signal",
+        offset
+    );
+    let signal: Vec<i32> = (offset..len).map(|i| digits[i % digits.len()]).collect();
+    #[cfg(feature = "rayon")]
+    let signal = fft_par(signal, offset, 100);
+    #[cfg(not(feature = "rayon"))]
+    let signal = fft(signal, offset, 100);
+    join(&signal[..8], "")
+}
+
+pub fn part1(input: &str) -> String {
+    let digits = fft(parse_digits(input), 0, 100);
     join(&digits[0..8], "")
 }
 
 pub fn part2(input: &str) -> String {
     let msg_offset: usize = input[0..7].parse().unwrap();
-    let digits: Vec<i32> = input
-        .trim()
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as i32)
-        .collect();
-    let mut digits: Vec<i32> = std::iter::repeat(digits.clone())
-        .take(10_000)
-        .flatten()
-        .skip(msg_offset)
-        .collect();
-    let size = digits.len();
-    for _phase in 0..100 {
-        for i in (0..size - 1).rev() {
-            digits[i] += digits[i + 1];
+    decode(&parse_digits(input), 10_000, msg_offset)
+}
+
+impl_solution!(Day16, 16);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_naive() {
+        let digits = parse_digits("80871224585914546619083218645595");
+        for phases in 0..5 {
+            let naive = fft_naive(digits.clone(), phases);
+            for offset in 0..digits.len() {
+                let fast = fft(digits[offset..].to_vec(), offset, phases);
+                assert_eq!(&naive[offset..], &fast[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!("24176176", part1("80871224585914546619083218645595"));
+        assert_eq!("84462026", part2("03036732577212944063491565474664"));
+        // the message can be anywhere, even in the first half
+        let digits = parse_digits("19617804207202209144916044189917");
+        let naive = fft_naive(digits.repeat(5), 100);
+        for &offset in [0, 7, 80, 152].iter() {
+            assert_eq!(
+                join(&naive[offset..offset + 8], ""),
+                decode(&digits, 5, offset)
+            );
         }
-        digits.iter_mut().for_each(|x| *x %= 10);
     }
-    join(&digits[..8], "")
 }
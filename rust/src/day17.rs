@@ -1,58 +1,329 @@
-use crate::{intcode::{IntcodeMachine, run_from_str}, util::Matrix};
-use itertools::iproduct;
+use crate::{
+    intcode::{AsciiIo, IntcodeMachine},
+    util::{Direction, Grid, Point},
+};
 
-fn surrounding(
-    x: usize,
-    y: usize,
-) -> (
-    (usize, usize),
-    (usize, usize),
-    (usize, usize),
-    (usize, usize),
-) {
-    ((x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y))
+/// Movement functions and the main routine can each be at most this
+/// many characters long, not counting the newline.
+const MAX_ROUTINE_LEN: usize = 20;
+
+/// What the vacuum robot's camera sees.
+struct Camera {
+    view: Grid<char>,
 }
 
-const ROPE: i64 = '#' as i64;
-const NEWLINE: i64 = '\n' as i64;
+impl Camera {
+    /// Builds the view from the lines output by the camera. Anything
+    /// other than the map itself (like the prompts printed when the
+    /// robot is woken up) is skipped.
+    fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let rows: Vec<&str> = lines
+            .into_iter()
+            .filter(|l| !l.is_empty() && l.chars().all(|c| "#.^v<>X".contains(c)))
+            .collect();
+        let view = Grid::parse(&rows.join("\n"), |c| c);
+        Camera { view }
+    }
 
-pub fn part1(input: &str) -> usize {
-    let mut screen = Vec::with_capacity(3000);
-    run_from_str(input, &mut std::io::empty(), &mut screen);
-    let line_width = screen.iter().position(|c| *c == NEWLINE).unwrap() + 1;
-    let grid = Matrix::wrap(screen, line_width);
-    let mut alignment_sum = 0;
-    for (y, x) in iproduct!(1..grid.width() - 1, 1..grid.height() - 1) {
-        if grid[(x, y)] == ROPE {
-            let (u, d, l, r) = surrounding(x, y);
-            if grid[u] == ROPE && grid[d] == ROPE && grid[l] == ROPE && grid[r] == ROPE {
-                alignment_sum += x * y;
+    fn is_scaffold(&self, pos: Point) -> bool {
+        self.view.get(pos).is_some_and(|&c| c != '.')
+    }
+
+    fn intersections(&self) -> Vec<Point> {
+        self.view
+            .positions()
+            .filter(|&p| self.is_scaffold(p))
+            .filter(|&p| {
+                self.view
+                    .neighbors4(p)
+                    .filter(|&n| self.is_scaffold(n))
+                    .count()
+                    == 4
+            })
+            .collect()
+    }
+
+    fn robot(&self) -> (Point, Direction) {
+        self.view
+            .iter()
+            .find_map(|(pos, &c)| Direction::all().find(|d| d.arrow() == c).map(|d| (pos, d)))
+            .expect("Robot not found in camera view")
+    }
+
+    /// Follows the scaffold from the robot's position until its end,
+    /// returning each turn and the number of steps taken after it,
+    /// like `L,10`. The robot only turns when it can't go forward
+    /// anymore, which covers the whole scaffold as long as it has no
+    /// branches other than intersections. If the robot starts facing
+    /// away from the scaffold, the path begins by turning around with
+    /// `R,R`.
+    fn trace_path(&self) -> Vec<String> {
+        let (mut pos, mut dir) = self.robot();
+        let mut path = Vec::new();
+        let mut turn: Option<String> = None;
+        let mut started = false;
+        loop {
+            let mut steps = 0;
+            while self.is_scaffold(pos + dir) {
+                pos += dir;
+                steps += 1;
+            }
+            match (turn.take(), steps) {
+                (Some(t), _) => path.push(format!("{},{}", t, steps)),
+                (None, 0) => (),
+                (None, _) => path.push(steps.to_string()),
+            }
+            started |= steps > 0;
+            // never turn back the way we came, which only the start
+            // position doesn't have
+            let next = Direction::all()
+                .filter(|&d| !started || d != dir.opposite())
+                .find(|&d| self.is_scaffold(pos + d));
+            match next {
+                Some(next) => {
+                    turn = dir.turn_to(next).map(|t| match t.letter() {
+                        Some(letter) => letter.to_string(),
+                        None => "R,R".to_string(),
+                    });
+                    dir = next;
+                }
+                None => return path,
+            }
+        }
+    }
+}
+
+/// A path split into a main routine and up to three movement
+/// functions.
+#[derive(Debug)]
+struct Routines {
+    main: Vec<usize>,
+    functions: Vec<String>,
+}
+
+impl Routines {
+    /// Finds movement functions that can be combined to walk the whole
+    /// path, with every routine fitting within the length limit.
+    fn compress(path: &[String]) -> Option<Self> {
+        let mut main = Vec::new();
+        let mut functions = Vec::new();
+        if compress_rest(path, &mut functions, &mut main) {
+            Some(Routines {
+                main,
+                functions: functions.iter().map(|f| f.join(",")).collect(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The input for the robot: main routine, functions, and the
+    /// answer to whether to show a video feed.
+    fn to_input(&self) -> String {
+        let main: Vec<_> = self
+            .main
+            .iter()
+            .map(|&i| ((b'A' + i as u8) as char).to_string())
+            .collect();
+        let mut input = main.join(",") + "\n";
+        for i in 0..3 {
+            input += self.functions.get(i).map(String::as_str).unwrap_or("");
+            input += "\n";
+        }
+        input += "n\n";
+        input
+    }
+}
+
+fn routine_len(commands: &[String]) -> usize {
+    commands.iter().map(String::len).sum::<usize>() + commands.len().saturating_sub(1)
+}
+
+/// Tries to cover `rest` using the functions found so far, or by
+/// adding new ones starting at its beginning.
+fn compress_rest<'a>(
+    rest: &'a [String],
+    functions: &mut Vec<&'a [String]>,
+    main: &mut Vec<usize>,
+) -> bool {
+    if rest.is_empty() {
+        return true;
+    }
+    // each call in main takes up a letter and a comma
+    if 2 * (main.len() + 1) - 1 > MAX_ROUTINE_LEN {
+        return false;
+    }
+    for i in 0..functions.len() {
+        let f = functions[i];
+        if rest.starts_with(f) {
+            main.push(i);
+            if compress_rest(&rest[f.len()..], functions, main) {
+                return true;
             }
+            main.pop();
         }
     }
-    alignment_sum
+    if functions.len() < 3 {
+        for len in 1..=rest.len() {
+            let candidate = &rest[..len];
+            if routine_len(candidate) > MAX_ROUTINE_LEN {
+                break;
+            }
+            main.push(functions.len());
+            functions.push(candidate);
+            if compress_rest(&rest[len..], functions, main) {
+                return true;
+            }
+            functions.pop();
+            main.pop();
+        }
+    }
+    false
+}
+
+pub fn part1(input: &str) -> usize {
+    let mut camera = IntcodeMachine::from_str(input);
+    let mut io = AsciiIo::new();
+    io.run(&mut camera);
+    let lines: Vec<_> = io.drain_events().filter_map(|e| e.line()).collect();
+    let camera = Camera::from_lines(lines.iter().map(String::as_str));
+    camera
+        .intersections()
+        .iter()
+        .map(|p| (p.x * p.y) as usize)
+        .sum()
 }
 
 pub fn part2(input: &str) -> i64 {
     let mut source_code: Vec<_> = input
         .trim()
-        .split(",")
+        .split(',')
         .map(|i| i.parse().unwrap())
         .collect();
+    // wake the robot up
     source_code[0] = 2;
-    let mut machine = IntcodeMachine::copy_program(&source_code);
-    let a_routine = "L,10,R,8,L,6,R,6\n";
-    let b_routine = "L,8,L,8,R,8\n";
-    let c_routine = "R,8,L,6,L,10,L,10\n";
-    let main_routine = "A,B,A,C,A,B,C,B,C,B\n";
-    let mut full = String::new();
-    full += main_routine;
-    full += a_routine;
-    full += b_routine;
-    full += c_routine;
-    full += "n\n";
-    let mut robot_input: Vec<i64> = full.chars().map(|c| c as i64).collect();
-    let mut out = Vec::new();
-    machine.run(&mut robot_input, &mut out);
-    *out.last().unwrap()
+    let mut robot = IntcodeMachine::copy_program(&source_code);
+    let mut io = AsciiIo::new();
+    // the robot shows the camera view before asking for its routines
+    io.run(&mut robot);
+    io.flush();
+    let lines: Vec<_> = io.drain_events().filter_map(|e| e.line()).collect();
+    let camera = Camera::from_lines(lines.iter().map(String::as_str));
+
+    let path = camera.trace_path();
+    let routines = Routines::compress(&path).expect("Path can't be split into routines");
+    io.push_str(&routines.to_input());
+    io.run(&mut robot);
+    // the amount of dust collected is the only non-ASCII output
+    io.drain_events()
+        .filter_map(|e| e.value())
+        .last()
+        .expect("Robot didn't report collected dust")
+}
+
+impl_solution!(Day17, 17);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/17-1.txt");
+
+    const PATH_EXAMPLE: &str = "\
+#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......";
+
+    fn path(s: &str) -> Vec<String> {
+        let commands: Vec<_> = s.split(',').collect();
+        commands.chunks(2).map(|c| c.join(",")).collect()
+    }
+
+    /// Walks the main routine, checking every routine fits, and
+    /// returns the commands it makes the robot follow.
+    fn expand(routines: &Routines) -> String {
+        assert!(routines.functions.len() <= 3);
+        assert!(routines.main.len() * 2 - 1 <= MAX_ROUTINE_LEN);
+        assert!(routines
+            .functions
+            .iter()
+            .all(|f| f.len() <= MAX_ROUTINE_LEN));
+        let main: Vec<_> = routines
+            .main
+            .iter()
+            .map(|&i| routines.functions[i].as_str())
+            .collect();
+        main.join(",")
+    }
+
+    #[test]
+    fn test_intersections() {
+        let view = "..#..........\n..#..........\n#######...###\n#.#...#...#.#\n\
+                    #############\n..#...#...#..\n..#####...^..";
+        let camera = Camera::from_lines(view.lines());
+        let alignment: i32 = camera.intersections().iter().map(|p| p.x * p.y).sum();
+        assert_eq!(76, alignment);
+    }
+
+    #[test]
+    fn test_trace_path() {
+        // the prompt the robot prints after the view is skipped
+        let lines = PATH_EXAMPLE.lines().chain(vec!["", "Main:"]);
+        let camera = Camera::from_lines(lines);
+        assert_eq!(
+            path("R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"),
+            camera.trace_path()
+        );
+    }
+
+    #[test]
+    fn test_trace_path_turning_around() {
+        let view = "..#####\n..#...#\n..v...#\n......#";
+        let camera = Camera::from_lines(view.lines());
+        let path = camera.trace_path();
+        assert_eq!(vec!["R,R,2", "R,4", "R,3"], path);
+        let routines = Routines::compress(&path).unwrap();
+        assert_eq!(path.join(","), expand(&routines));
+        assert!(routines.to_input().contains("R,R,2"));
+
+        let camera = Camera::from_lines(view.replace('v', "<").lines());
+        assert_eq!(vec!["R,2", "R,4", "R,3"], camera.trace_path());
+    }
+
+    #[test]
+    fn test_compress() {
+        let example = path("R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2");
+        let routines = Routines::compress(&example).unwrap();
+        assert_eq!(example.join(","), expand(&routines));
+        let input = routines.to_input();
+        assert_eq!(5, input.lines().count());
+        assert!(input.ends_with("\nn\n"));
+
+        // at most four of these fit in a function, and none repeat
+        let distinct: Vec<_> = (10..23).map(|n| format!("L,{}", n)).collect();
+        assert!(Routines::compress(&distinct[..12]).is_some());
+        assert!(Routines::compress(&distinct).is_none());
+        // ten calls of at most five commands each
+        let same = vec!["R,1".to_string(); 51];
+        assert!(Routines::compress(&same[..50]).is_some());
+        assert!(Routines::compress(&same).is_none());
+    }
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(2660, part1(INPUT));
+        assert_eq!(790595, part2(INPUT));
+    }
 }
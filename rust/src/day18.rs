@@ -0,0 +1,276 @@
+//! Day 18: Many-Worlds Interpretation
+//!
+//! # Problem Description
+//!
+//! A vault full of keys and doors, where each door needs the key with
+//! the same letter. Part 1 asks for the fewest steps needed to collect
+//! every key starting from the entrance. In part 2 the vault is split
+//! into four sections, each with its own robot, and only one robot
+//! moves at a time.
+//!
+//! # Implementation Details
+//!
+//! The grid is first turned into a graph whose nodes are the
+//! entrances, keys and doors, with edges for the paths between them
+//! that don't go through any other node. The search then runs
+//! Dijkstra over states made of every robot's position and the set of
+//! keys collected so far, where a move takes one robot to a key it
+//! can reach without going through a locked door.
+
+use std::collections::HashMap;
+
+use petgraph::{
+    graph::{NodeIndex, UnGraph},
+    visit::EdgeRef,
+};
+
+use crate::util::{search, Grid, Point};
+
+/// One bit per key, `a` being the lowest.
+type KeySet = u32;
+
+/// Keys reachable from somewhere, with the node they're at and the
+/// distance to them.
+type Targets = Vec<(u8, NodeIndex, usize)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Poi {
+    Entrance,
+    Key(u8),
+    Door(u8),
+}
+
+impl Poi {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '@' => Some(Poi::Entrance),
+            'a'..='z' => Some(Poi::Key(c as u8 - b'a')),
+            'A'..='Z' => Some(Poi::Door(c as u8 - b'A')),
+            _ => None,
+        }
+    }
+}
+
+/// Splits the vault into four sections by walling off the area around
+/// its single entrance and placing an entrance in each corner of it.
+/// Vaults that already have more than one entrance are left alone.
+fn split_entrance(grid: &mut Grid<char>) {
+    let entrances: Vec<_> = grid.find_all(&'@').collect();
+    if let [entrance] = entrances[..] {
+        let pattern = ["@#@", "###", "@#@"];
+        for (dy, row) in pattern.iter().enumerate() {
+            for (dx, c) in row.chars().enumerate() {
+                grid[entrance + Point::new(dx as i32 - 1, dy as i32 - 1)] = c;
+            }
+        }
+    }
+}
+
+struct Vault {
+    graph: UnGraph<Poi, usize>,
+    entrances: Vec<NodeIndex>,
+    all_keys: KeySet,
+}
+
+impl Vault {
+    fn from_grid(grid: &Grid<char>) -> Self {
+        let mut graph = UnGraph::new_undirected();
+        let mut nodes = HashMap::new();
+        let mut entrances = Vec::new();
+        let mut all_keys = 0;
+        for (pos, &c) in grid.iter() {
+            if let Some(poi) = Poi::from_char(c) {
+                let node = graph.add_node(poi);
+                nodes.insert(pos, node);
+                match poi {
+                    Poi::Entrance => entrances.push(node),
+                    Poi::Key(k) => all_keys |= 1 << k,
+                    Poi::Door(_) => (),
+                }
+            }
+        }
+        // walk out from every node, stopping at the next ones found
+        for (&start, &node) in nodes.iter() {
+            let neighbors = |&pos: &Point| {
+                let stop = pos != start && nodes.contains_key(&pos);
+                grid.neighbors4(pos)
+                    .filter(move |&next| !stop && grid[next] != '#')
+            };
+            for (pos, d) in search::bfs(Some(start), neighbors) {
+                // each path is found from both of its ends, so only one
+                // of them adds it
+                match nodes.get(&pos) {
+                    Some(&other) if node < other => {
+                        graph.add_edge(node, other, d);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Vault {
+            graph,
+            entrances,
+            all_keys,
+        }
+    }
+
+    /// Keys not yet collected that can be reached from `from` holding
+    /// `keys`.
+    fn reachable_keys(&self, from: NodeIndex, keys: KeySet) -> Targets {
+        let missing = |node: NodeIndex| match self.graph[node] {
+            Poi::Key(k) | Poi::Door(k) => keys & (1 << k) == 0,
+            Poi::Entrance => false,
+        };
+        // paths stop at keys not collected yet and at locked doors
+        let neighbors = |&node: &NodeIndex| {
+            let stop = missing(node);
+            self.graph
+                .edges(node)
+                .filter(move |_| !stop)
+                .map(move |edge| {
+                    let next = if edge.source() == node {
+                        edge.target()
+                    } else {
+                        edge.source()
+                    };
+                    (next, *edge.weight())
+                })
+        };
+        search::dijkstra(Some(from), neighbors)
+            .filter_map(|(node, d)| match self.graph[node] {
+                Poi::Key(k) if missing(node) => Some((k, node, d)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Fewest total steps for the robots to collect every key.
+    fn collect_keys(&self) -> Option<usize> {
+        type State = (Vec<NodeIndex>, KeySet);
+        let mut reachable: HashMap<(NodeIndex, KeySet), Targets> = HashMap::new();
+        let neighbors = |(robots, keys): &State| {
+            let mut next = Vec::new();
+            for (i, &robot) in robots.iter().enumerate() {
+                let targets = reachable
+                    .entry((robot, *keys))
+                    .or_insert_with(|| self.reachable_keys(robot, *keys));
+                for &(key, node, steps) in targets.iter() {
+                    let mut next_robots = robots.clone();
+                    next_robots[i] = node;
+                    next.push(((next_robots, keys | (1 << key)), steps));
+                }
+            }
+            next
+        };
+        search::dijkstra(Some((self.entrances.clone(), 0)), neighbors)
+            .find(|((_, keys), _)| *keys == self.all_keys)
+            .map(|(_, d)| d)
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    let vault = Vault::from_grid(&Grid::parse(input, |c| c));
+    vault
+        .collect_keys()
+        .expect("Not every key can be collected")
+}
+
+pub fn part2(input: &str) -> usize {
+    let mut grid = Grid::parse(input, |c| c);
+    split_entrance(&mut grid);
+    let vault = Vault::from_grid(&grid);
+    vault
+        .collect_keys()
+        .expect("Not every key can be collected")
+}
+
+impl_solution!(Day18, 18);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part1() {
+        let ex1 = "
+#########
+#b.A.@.a#
+#########";
+        let ex2 = "
+########################
+#f.D.E.e.C.b.A.@.a.B.c.#
+######################.#
+#d.....................#
+########################";
+        let ex3 = "
+########################
+#...............b.C.D.f#
+#.######################
+#.....@.a.B.c.d.A.e.F.g#
+########################";
+        let ex4 = "
+#################
+#i.G..c...e..H.p#
+########.########
+#j.A..b...f..D.o#
+########@########
+#k.E..a...g..B.n#
+########.########
+#l.F..d...h..C.m#
+#################";
+        let ex5 = "
+########################
+#@..............ac.GI.b#
+###d#e#f################
+###A#B#C################
+###g#h#i################
+########################";
+        assert_eq!(8, part1(ex1));
+        assert_eq!(86, part1(ex2));
+        assert_eq!(132, part1(ex3));
+        assert_eq!(136, part1(ex4));
+        assert_eq!(81, part1(ex5));
+    }
+
+    #[test]
+    fn test_part2() {
+        let ex1 = "
+#######
+#a.#Cd#
+##...##
+##.@.##
+##...##
+#cB#Ab#
+#######";
+        let ex2 = "
+###############
+#d.ABC.#.....a#
+######@#@######
+###############
+######@#@######
+#b.....#.....c#
+###############";
+        let ex3 = "
+#############
+#DcBa.#.GhKl#
+#.###@#@#I###
+#e#d#####j#k#
+###C#@#@###J#
+#fEbA.#.FgHi#
+#############";
+        let ex4 = "
+#############
+#g#f.D#..h#l#
+#F###e#E###.#
+#dCba@#@BcIJ#
+#############
+#nK.L@#@G...#
+#M###N#H###.#
+#o#m..#i#jk.#
+#############";
+        assert_eq!(8, part2(ex1));
+        assert_eq!(24, part2(ex2));
+        assert_eq!(32, part2(ex3));
+        assert_eq!(72, part2(ex4));
+    }
+}
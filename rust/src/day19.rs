@@ -1,63 +1,226 @@
-use crate::intcode::IntcodeMachine;
-use itertools::iproduct;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
 
-struct BeamDetector {
-    program: IntcodeMachine,
+use crate::{intcode::IntcodeMachine, util::Point};
+
+/// How far right of the origin the beam is assumed to be able to go
+/// for each row down. Rows with nothing in the beam up to that point are
+/// considered empty.
+const MAX_SLOPE: i64 = 10;
+
+/// Something that can tell whether a position is in the beam.
+pub trait Drone {
+    fn deploy(&mut self, pos: Point<i64>) -> bool;
 }
 
-impl BeamDetector {
-    fn within_beam(&self, x: i64, y: i64) -> bool {
-        let mut program = self.program.clone();
-        let mut input = vec![x, y];
+/// The drone system's program, run once per position.
+impl Drone for IntcodeMachine {
+    fn deploy(&mut self, pos: Point<i64>) -> bool {
+        let mut program = self.clone();
+        let mut input = vec![pos.x, pos.y];
         let mut out = None;
         program.run_while_input(&mut input, &mut out);
-        match out {
-            Some(1) => true,
-            _ => false,
-        }
+        out == Some(1)
     }
 }
 
-pub fn part1(input: &str) -> u32 {
-    let detector = BeamDetector {
-        program: IntcodeMachine::from_str(input),
-    };
-    let mut count = 0;
-    for (y, x) in iproduct!(0..50, 0..50) {
-        if detector.within_beam(x, y) {
-            count += 1;
+impl<F: FnMut(Point<i64>) -> bool> Drone for F {
+    fn deploy(&mut self, pos: Point<i64>) -> bool {
+        self(pos)
+    }
+}
+
+/// The tractor beam as seen by the drone system. Each row of the beam
+/// is a single run of cells whose edges only move right going down,
+/// so once a row is known the rows around it can be found by probing
+/// just around its edges.
+pub struct TractorBeam<D> {
+    drone: D,
+    probed: HashMap<Point<i64>, bool>,
+    probes: usize,
+    /// The cells of each row learned so far that are in the beam, or
+    /// `None` if the row is empty.
+    rows: BTreeMap<i64, Option<Range<i64>>>,
+}
+
+impl<D: Drone> TractorBeam<D> {
+    pub fn new(drone: D) -> Self {
+        TractorBeam {
+            drone,
+            probed: HashMap::new(),
+            probes: 0,
+            rows: BTreeMap::new(),
         }
     }
-    count
+
+    /// Number of times a drone has been deployed so far.
+    pub fn probes(&self) -> usize {
+        self.probes
+    }
+
+    /// Deploys a drone to `pos`, unless it's been there before.
+    pub fn probe(&mut self, pos: Point<i64>) -> bool {
+        if pos.x < 0 || pos.y < 0 {
+            return false;
+        }
+        if let Some(&pulled) = self.probed.get(&pos) {
+            return pulled;
+        }
+        self.probes += 1;
+        let pulled = self.drone.deploy(pos);
+        self.probed.insert(pos, pulled);
+        pulled
+    }
+
+    /// The cells of row `y` that are in the beam, from its left edge
+    /// to one past its right edge, or `None` if there are none.
+    pub fn edges(&mut self, y: i64) -> Option<Range<i64>> {
+        if y < 0 {
+            return None;
+        }
+        if let Some(edges) = self.rows.get(&y) {
+            return edges.clone();
+        }
+        // the closest known row above starts and ends no further right
+        // than this one, and the closest one below no further left, so
+        // the row is somewhere between them
+        let above = self
+            .rows
+            .range(..y)
+            .rev()
+            .find_map(|(_, edges)| edges.clone())
+            .unwrap_or(0..0);
+        let below = self
+            .rows
+            .range(y + 1..)
+            .find_map(|(_, edges)| edges.clone());
+        let limit = below.as_ref().map_or(MAX_SLOPE * (y + 1) + 1, |b| b.end);
+        let edges = (above.start..limit)
+            .find(|&x| self.probe(Point::new(x, y)))
+            .map(|left| {
+                let mut right = left.max(above.end - 1);
+                while right < limit && self.probe(Point::new(right, y)) {
+                    right += 1;
+                }
+                left..right
+            });
+        self.rows.insert(y, edges.clone());
+        edges
+    }
+
+    pub fn contains(&mut self, pos: Point<i64>) -> bool {
+        self.edges(pos.y).is_some_and(|row| row.contains(&pos.x))
+    }
+
+    pub fn width(&mut self, y: i64) -> i64 {
+        self.edges(y).map_or(0, |row| row.end - row.start)
+    }
+
+    /// Finds the top left corner of the closest `size`x`size` square
+    /// that fits entirely in the beam.
+    pub fn first_square(&mut self, size: i64) -> Point<i64> {
+        // going down row by row, the square's bottom left corner is at
+        // the left edge, and it fits if its top right corner is in the
+        // beam too
+        for bottom in size - 1.. {
+            let top = bottom - size + 1;
+            if let Some(row) = self.edges(bottom) {
+                if self.contains(Point::new(row.start + size - 1, top)) {
+                    return Point::new(row.start, top);
+                }
+            }
+        }
+        unreachable!()
+    }
+}
+
+pub fn part1(input: &str) -> u32 {
+    let mut beam = TractorBeam::new(IntcodeMachine::from_str(input));
+    (0..50)
+        .filter_map(|y| beam.edges(y))
+        .map(|row| (row.end.min(50) - row.start.min(50)) as u32)
+        .sum()
 }
 
 pub fn part2(input: &str) -> i64 {
-    let detector = BeamDetector {
-        program: IntcodeMachine::from_str(input),
-    };
-    // given this example (looking for a 4x4)
-    // # # # # B . .
-    // # # # # # . .
-    // . # # # # # .
-    // . A # # # # #
-    // we're walking until we find an A such that B is also within the beam.
-
-    // starting at y = 99 because the square has to be 100 tall
-    let (mut x, mut y) = (0, 99);
-    loop {
-        // continue walking right until a tile within the beam is
-        // found in this line
-        if !detector.within_beam(x, y) {
-            x += 1;
-            continue;
-        }
-        // if B is not within the beam, we walk down to the next line
-        if !detector.within_beam(x + 99, y - 99) {
-            y += 1;
-            continue;
-        }
-        // the answer is based on the x,y of the top left, so we have
-        // to subtract from y to get the right value
-        return 10000 * x + y - 99;
+    let mut beam = TractorBeam::new(IntcodeMachine::from_str(input));
+    let corner = beam.first_square(100);
+    10000 * corner.x + corner.y
+}
+
+impl_solution!(Day19, 19);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A beam between the lines x = 0.7y and x = 0.9y, which leaves
+    /// some rows near the top empty.
+    fn in_beam(pos: Point<i64>) -> bool {
+        7 * pos.y <= 10 * pos.x && 10 * pos.x <= 9 * pos.y
+    }
+
+    fn row(y: i64) -> Option<Range<i64>> {
+        let mut cells = (0..=y).filter(|&x| in_beam(Point::new(x, y)));
+        let left = cells.next()?;
+        Some(left..cells.next_back().unwrap_or(left) + 1)
+    }
+
+    #[test]
+    fn test_edges() {
+        assert_eq!(None, row(1));
+        let mut beam = TractorBeam::new(in_beam);
+        for y in 0..60 {
+            assert_eq!(row(y), beam.edges(y));
+        }
+        // rows asked for out of order are narrowed down from both sides
+        let mut beam = TractorBeam::new(in_beam);
+        for &y in [50, 10, 30, 29, 31, 3, 1, 0, 60].iter() {
+            assert_eq!(row(y), beam.edges(y));
+        }
+        assert!(beam.contains(Point::new(20, 25)));
+        assert!(!beam.contains(Point::new(17, 25)));
+        assert_eq!(5, beam.width(25));
+    }
+
+    /// Finds the square by checking every corner of every candidate,
+    /// returning it along with the number of probes that took.
+    fn brute_force_square(size: i64) -> (Point<i64>, usize) {
+        let mut probes = 0;
+        let mut probe = |x, y| {
+            probes += 1;
+            in_beam(Point::new(x, y))
+        };
+        for y in 0.. {
+            for x in 0..=MAX_SLOPE * (y + 1) {
+                let far = size - 1;
+                if probe(x, y) && probe(x + far, y) && probe(x, y + far) && probe(x + far, y + far)
+                {
+                    return (Point::new(x, y), probes);
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_first_square() {
+        for &size in [1, 2, 5, 10, 30].iter() {
+            let mut beam = TractorBeam::new(in_beam);
+            let (corner, brute_probes) = brute_force_square(size);
+            assert_eq!(corner, beam.first_square(size));
+            // following the edges only probes around them, instead of
+            // scanning each row from the left, which pays off once the
+            // square is away from the origin
+            assert!(
+                size < 10 || beam.probes() * 10 < brute_probes,
+                "size {}: {} probes following the edges, {} by brute force",
+                size,
+                beam.probes(),
+                brute_probes
+            );
+        }
     }
 }
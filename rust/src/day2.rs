@@ -50,3 +50,5 @@ pub fn part2(input: &str) -> i64 {
     }
     unreachable!("It's assumed the puzzle will have _a_ valid solution");
 }
+
+impl_solution!(Day2, 2);
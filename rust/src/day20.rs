@@ -0,0 +1,273 @@
+//! Day 20: Donut Maze
+//!
+//! # Problem Description
+//!
+//! A maze shaped like a donut, with portals marked by two-letter
+//! labels around its inner and outer edges. Each label appears twice,
+//! and stepping onto a tile next to one of them teleports you to the
+//! tile next to the other. Part 1 asks for the shortest path from `AA`
+//! to `ZZ`. In part 2 the maze is recursive: inner portals lead one
+//! level down into a copy of the maze and outer ones lead one level
+//! back up, and the exit is only open on the outermost level.
+//!
+//! # Implementation Details
+//!
+//! Portals are found by looking for pairs of letters next to each
+//! other, and are on the outer side if their tile is on the edge of
+//! the donut. Both parts are then a BFS over (tile, level) states, with
+//! the level always being 0 for part 1.
+
+use std::collections::HashMap;
+
+use crate::util::{search, Grid, Point};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Inner,
+    Outer,
+}
+
+type Pos = Point;
+
+struct Maze {
+    grid: Grid<char>,
+    /// Where each tile next to a portal leads, and which side of the
+    /// donut the portal it's next to is on.
+    portals: HashMap<Pos, (Pos, Side)>,
+    start: Pos,
+    end: Pos,
+}
+
+impl Maze {
+    fn parse(input: &str) -> Self {
+        // lines start with spaces that matter, but may not be padded
+        // all the way to the right, which the grid takes care of
+        let grid = Grid::parse(input, |c| c);
+        let (width, height) = (grid.width() as i32, grid.height() as i32);
+
+        let is_letter = |pos: Pos| grid.get(pos).is_some_and(char::is_ascii_uppercase);
+        let is_open = |pos: Pos| grid.get(pos) == Some(&'.');
+        let mut labels: HashMap<String, Vec<(Pos, Side)>> = HashMap::new();
+        for pos in grid.positions() {
+            if !is_letter(pos) {
+                continue;
+            }
+            // a label is read left to right or top to bottom, with its
+            // tile right before or right after it
+            let along = [Point::new(1, 0), Point::new(0, 1)]
+                .iter()
+                .copied()
+                .find(|&d| is_letter(pos + d));
+            let (second, before, after) = match along {
+                Some(d) => (pos + d, pos - d, pos + d * 2),
+                None => continue,
+            };
+            let tile = if is_open(before) {
+                before
+            } else if is_open(after) {
+                after
+            } else {
+                panic!("Label at {} isn't next to an open tile", pos);
+            };
+            let Point { x: tx, y: ty } = tile;
+            let side = if tx == 2 || ty == 2 || tx == width - 3 || ty == height - 3 {
+                Side::Outer
+            } else {
+                Side::Inner
+            };
+            let label: String = [grid[pos], grid[second]].iter().collect();
+            labels.entry(label).or_default().push((tile, side));
+        }
+
+        let mut endpoint = |label: &str| match labels.remove(label).as_deref() {
+            Some(&[(tile, _)]) => tile,
+            _ => panic!("Maze needs exactly one {}", label),
+        };
+        let start = endpoint("AA");
+        let end = endpoint("ZZ");
+        let mut portals = HashMap::new();
+        for (label, ends) in labels {
+            match ends[..] {
+                [(a, side_a), (b, side_b)] => {
+                    portals.insert(a, (b, side_a));
+                    portals.insert(b, (a, side_b));
+                }
+                _ => panic!("Portal {} doesn't have two ends", label),
+            }
+        }
+        Maze {
+            grid,
+            portals,
+            start,
+            end,
+        }
+    }
+
+    /// Length of the shortest path from `AA` to `ZZ`, going through
+    /// portals in the recursive way or not.
+    fn shortest_path(&self, recursive: bool) -> Option<usize> {
+        // without a limit a maze with no way out would be searched
+        // forever, one level deeper at a time. Each level taken down has
+        // to be climbed back through an outer portal, so paths that
+        // need to go deeper than there are portals are assumed not to
+        // exist. That's a guess rather than a proof, but it holds for
+        // every example and input seen so far
+        let max_level = self.portals.len();
+        let neighbors = |&(pos, level): &(Pos, usize)| {
+            let mut next = Vec::with_capacity(5);
+            for n in self.grid.neighbors4(pos) {
+                if self.grid[n] == '.' {
+                    next.push((n, level));
+                }
+            }
+            if let Some(&(target, side)) = self.portals.get(&pos) {
+                match (recursive, side) {
+                    (false, _) => next.push((target, level)),
+                    (true, Side::Inner) if level < max_level => next.push((target, level + 1)),
+                    (true, Side::Outer) if level > 0 => next.push((target, level - 1)),
+                    _ => (),
+                }
+            }
+            next
+        };
+        search::bfs(Some((self.start, 0)), neighbors)
+            .find(|&(state, _)| state == (self.end, 0))
+            .map(|(_, dist)| dist)
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    Maze::parse(input)
+        .shortest_path(false)
+        .expect("No path from AA to ZZ")
+}
+
+pub fn part2(input: &str) -> usize {
+    Maze::parse(input)
+        .shortest_path(true)
+        .expect("No path from AA to ZZ")
+}
+
+impl_solution!(Day20, 20);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EX1: &str = "
+         A
+         A
+  #######.#########
+  #######.........#
+  #######.#######.#
+  #######.#######.#
+  #######.#######.#
+  #####  B    ###.#
+BC...##  C    ###.#
+  ##.##       ###.#
+  ##...DE  F  ###.#
+  #####    G  ###.#
+  #########.#####.#
+DE..#######...###.#
+  #.#########.###.#
+FG..#########.....#
+  ###########.#####
+             Z
+             Z
+";
+
+    const EX2: &str = "
+                   A
+                   A
+  #################.#############
+  #.#...#...................#.#.#
+  #.#.#.###.###.###.#########.#.#
+  #.#.#.......#...#.....#.#.#...#
+  #.#########.###.#####.#.#.###.#
+  #.............#.#.....#.......#
+  ###.###########.###.#.#.#.#.###
+  #.....#        A   C    #.#.#.#
+  #######        S   P    #####.#
+  #.#...#                 #......VT
+  #.#.#.#                 #.#####
+  #...#.#               YN....#.#
+  #.###.#                 #####.#
+DI....#.#                 #.....#
+  #####.#                 #.###.#
+ZZ......#               QG....#..AS
+  ###.###                 #######
+JO..#.#.#                 #.....#
+  #.#.#.#                 ###.#.#
+  #...#..DI             BU....#..LF
+  #####.#                 #.#####
+YN......#               VT..#....QG
+  #.###.#                 #.###.#
+  #.#...#                 #.....#
+  ###.###    J L     J    #.#.###
+  #.....#    O F     P    #.#...#
+  #.###.#####.#.#####.#####.###.#
+  #...#.#.#...#.....#.....#.#...#
+  #.#####.###.###.#.#.#########.#
+  #...#.#.....#...#.#.#.#.....#.#
+  #.###.#####.###.###.#.#.#######
+  #.#.........#...#.............#
+  #########.###.###.#############
+           B   J   C
+           U   P   P
+";
+
+    const EX3: &str = "
+             Z L X W       C
+             Z P Q B       K
+  ###########.#.#.#.#######.###############
+  #...#.......#.#.......#.#.......#.#.#...#
+  ###.#.#.#.#.#.#.#.###.#.#.#######.#.#.###
+  #.#...#.#.#...#.#.#...#...#...#.#.......#
+  #.###.#######.###.###.#.###.###.#.#######
+  #...#.......#.#...#...#.............#...#
+  #.#########.#######.#.#######.#######.###
+  #...#.#    F       R I       Z    #.#.#.#
+  #.###.#    D       E C       H    #.#.#.#
+  #.#...#                           #...#.#
+  #.###.#                           #.###.#
+  #.#....OA                       WB..#.#..ZH
+  #.###.#                           #.#.#.#
+CJ......#                           #.....#
+  #######                           #######
+  #.#....CK                         #......IC
+  #.###.#                           #.###.#
+  #.....#                           #...#.#
+  ###.###                           #.#.#.#
+XF....#.#                         RF..#.#.#
+  #####.#                           #######
+  #......CJ                       NM..#...#
+  ###.#.#                           #.###.#
+RE....#.#                           #......RF
+  ###.###        X   X       L      #.#.#.#
+  #.....#        F   Q       P      #.#.#.#
+  ###.###########.###.#######.#########.###
+  #.....#...#.....#.......#...#.....#.#...#
+  #####.#.###.#######.#######.###.###.#.#.#
+  #.......#.......#.#.#.#.#...#...#...#.#.#
+  #####.###.#####.#.#.#.#.###.###.#.###.###
+  #.......#.....#.#...#...............#...#
+  #############.#.#.###.###################
+               A O F   N
+               A A D   M
+";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(23, part1(EX1));
+        assert_eq!(58, part1(EX2));
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(26, part2(EX1));
+        assert_eq!(396, part2(EX3));
+        // there's no way out of this one when recursive, which the
+        // search only finds out by not going deeper than `max_level`
+        assert_eq!(None, Maze::parse(EX2).shortest_path(true));
+    }
+}
@@ -1,32 +1,137 @@
-use crate::intcode::run_from_str;
-
-const PART1: &str =
-    "OR A J
-AND B J
-AND C J
-NOT J J
-AND D J
-WALK\n";
-const PART2: &str = "OR A T
-AND B T
-AND C T
-NOT T T
-OR E J
-OR H J
-AND T J
-AND D J
-RUN\n";
-    
+use crate::intcode::{AsciiEvent, AsciiIo, IntcodeMachine};
+
+pub mod springscript;
+
+use springscript::{Hull, Mode, Script};
+
+/// Runs the given springscript on the droid, returning the amount of
+/// hull damage it reports, or the hull it fell in if it doesn't make it
+/// across.
+fn run_script(input: &str, script: &Script) -> Result<i64, Hull> {
+    let mut droid = IntcodeMachine::from_str(input);
+    let mut io = AsciiIo::new();
+    io.push_str(&script.to_string());
+    io.run(&mut droid);
+    io.flush();
+    read_report(io.drain_events())
+}
+
+/// Makes sense of what the droid printed after running a script.
+fn read_report(events: impl IntoIterator<Item = AsciiEvent>) -> Result<i64, Hull> {
+    let mut text = Vec::new();
+    for event in events {
+        match event {
+            AsciiEvent::Value(damage) => return Ok(damage),
+            AsciiEvent::Line(line) => text.push(line),
+        }
+    }
+    match Hull::from_report(text.iter().map(String::as_str)) {
+        Some(hull) => Err(hull),
+        None => panic!(
+            "Couldn't make sense of the droid's report:\n{}",
+            text.join("\n")
+        ),
+    }
+}
+
+/// Searches for scripts until one gets the droid across, learning a
+/// new hull each time the droid falls. `droid` runs a script, like
+/// [`run_script`].
+fn survey(mode: Mode, mut droid: impl FnMut(&Script) -> Result<i64, Hull>) -> i64 {
+    let mut hulls = Vec::new();
+    loop {
+        let script = springscript::search(&hulls, mode)
+            .expect("No springscript gets across every known hull");
+        match droid(&script) {
+            Ok(damage) => return damage,
+            Err(hull) => {
+                assert!(
+                    !hulls.contains(&hull),
+                    "Droid fell in hull {} with a script that should cross it",
+                    hull
+                );
+                hulls.push(hull);
+            }
+        }
+    }
+}
+
 pub fn part1(input: &str) -> i64 {
-    let mut solution: Vec<_> = PART1.chars().map(|c| c as i64).collect();
-    let mut out = None;
-    run_from_str(input, &mut solution, &mut out);
-    out.unwrap()
+    survey(Mode::Walk, |script| run_script(input, script))
 }
 
 pub fn part2(input: &str) -> i64 {
-    let mut solution: Vec<_> = PART2.chars().map(|c| c as i64).collect();
-    let mut out = None;
-    run_from_str(input, &mut solution, &mut out);
-    out.unwrap()
+    survey(Mode::Run, |script| run_script(input, script))
+}
+
+impl_solution!(Day21, 21);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/21-1.txt");
+
+    /// Answers like the springdroid would, falling in the first of
+    /// `hulls` the script doesn't get across and counting its runs.
+    fn stub_droid<'a>(
+        hulls: &'a [&str],
+        runs: &'a mut usize,
+    ) -> impl FnMut(&Script) -> Result<i64, Hull> + 'a {
+        move |script| {
+            *runs += 1;
+            let fell = hulls
+                .iter()
+                .map(|h| h.parse::<Hull>().unwrap())
+                .find(|h| !script.survives(h));
+            let mut lines = vec!["Input instructions:".to_string(), String::new()];
+            lines.push("Walking...".to_string());
+            lines.push(String::new());
+            let mut events: Vec<_> = match fell {
+                Some(hull) => {
+                    let air = ".".repeat(hull.len());
+                    lines.push("Didn't make it across:".to_string());
+                    lines.push(String::new());
+                    lines.push(air.clone());
+                    lines.push(format!("@{}", &air[1..]));
+                    lines.push(hull.to_string());
+                    lines.into_iter().map(AsciiEvent::Line).collect()
+                }
+                None => {
+                    let mut events: Vec<_> = lines.into_iter().map(AsciiEvent::Line).collect();
+                    events.push(AsciiEvent::Value(1234));
+                    events
+                }
+            };
+            events.push(AsciiEvent::Line(String::new()));
+            read_report(events)
+        }
+    }
+
+    #[test]
+    fn test_survey() {
+        let hulls = [
+            "#####.###########",
+            "#####..#.########",
+            "#####...#########",
+        ];
+        let mut runs = 0;
+        assert_eq!(1234, survey(Mode::Walk, stub_droid(&hulls, &mut runs)));
+        // every failed run teaches it a new hull
+        assert!(runs <= hulls.len() + 1, "{} runs", runs);
+    }
+
+    #[test]
+    #[should_panic(expected = "No springscript gets across")]
+    fn test_survey_gives_up() {
+        // nothing jumps over four holes in a row
+        let mut runs = 0;
+        survey(Mode::Run, stub_droid(&["#....#"], &mut runs));
+    }
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(19357335, part1(INPUT));
+        assert_eq!(1140147758, part2(INPUT));
+    }
 }
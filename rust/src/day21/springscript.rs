@@ -0,0 +1,795 @@
+//! Springscript, the language the springdroid is programmed in.
+//!
+//! Besides parsing and printing scripts, this has a simulator for the
+//! droid's trip over a stretch of hull, and a [`search`] for scripts
+//! that get the droid across a given set of hulls.
+//!
+//! # Search
+//!
+//! The droid decides whether to jump based only on what its sensors
+//! see, so a script is just a boolean function of the sensor
+//! readings. The search first walks the droid over every hull,
+//! branching on walking or jumping whenever it sees a new reading and
+//! dropping branches where it falls. Each way of getting across gives
+//! a table of readings where the droid has to jump and readings where
+//! it has to walk, with everything else left open.
+//!
+//! Each table is then turned into the cheapest function in
+//! conjunctive normal form that matches it, i.e. `J = C1 AND C2 AND
+//! ...` where each clause is an OR of sensors, possibly negated. With
+//! only `T` and `J` to work with, the first clause is built in `J`
+//! and can have any number of negated sensors, while the rest are
+//! built in `T` and can have at most one. Picking the clauses is a set
+//! cover problem: every reading where the droid must walk has to make
+//! some clause false, while no clause may be false for a reading where
+//! it must jump.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// Scripts can have at most this many instructions, not counting
+/// `WALK` or `RUN`.
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+/// Max number of literals in a single clause tried by the search.
+const MAX_CLAUSE_LEN: usize = 4;
+
+/// Max number of decision tables the search tries before settling for
+/// the best script found so far.
+const MAX_TABLES: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    T,
+    J,
+}
+
+impl Register {
+    /// The sensor registers, from the closest tile to the furthest.
+    pub const SENSORS: [Register; 9] = [
+        Register::A,
+        Register::B,
+        Register::C,
+        Register::D,
+        Register::E,
+        Register::F,
+        Register::G,
+        Register::H,
+        Register::I,
+    ];
+
+    /// How many tiles ahead a sensor register looks, or `None` for the
+    /// `T` and `J` registers.
+    pub fn distance(self) -> Option<usize> {
+        Register::SENSORS
+            .iter()
+            .position(|r| *r == self)
+            .map(|i| i + 1)
+    }
+
+    pub fn is_writable(self) -> bool {
+        self == Register::T || self == Register::J
+    }
+
+    fn to_char(self) -> char {
+        match self.distance() {
+            Some(d) => (b'A' + d as u8 - 1) as char,
+            None if self == Register::T => 'T',
+            None => 'J',
+        }
+    }
+}
+
+impl FromStr for Register {
+    type Err = ParseErrorKind;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let register = match s {
+            "T" => Register::T,
+            "J" => Register::J,
+            _ if s.len() == 1 && ("A"..="I").contains(&s) => {
+                Register::SENSORS[(s.as_bytes()[0] - b'A') as usize]
+            }
+            _ => return Err(ParseErrorKind::UnknownRegister(s.to_string())),
+        };
+        Ok(register)
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: Op,
+    pub src: Register,
+    pub dst: Register,
+}
+
+impl Instruction {
+    pub fn new(op: Op, src: Register, dst: Register) -> Self {
+        Instruction { op, src, dst }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+        write!(f, "{} {} {}", op, self.src, self.dst)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    /// How many sensors can be read in this mode.
+    pub fn sensor_range(self) -> usize {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+
+    fn sensor_mask(self) -> u16 {
+        (1 << self.sensor_range()) - 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnknownOp(String),
+    UnknownRegister(String),
+    WrongArgCount(usize),
+    ReadOnlyDestination(Register),
+    /// The sensor can't be read in the script's mode.
+    SensorOutOfRange(Register),
+    TooManyInstructions(usize),
+    /// The script doesn't end with `WALK` or `RUN`.
+    MissingMode,
+    /// There's something after `WALK` or `RUN`.
+    TrailingInput,
+    /// `WALK` or `RUN` is followed by something on the same line.
+    UnexpectedArgument(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line the error was found in.
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ParseErrorKind::*;
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            UnknownOp(op) => write!(f, "unknown instruction `{}`", op),
+            UnknownRegister(r) => write!(f, "unknown register `{}`", r),
+            WrongArgCount(n) => write!(f, "expected 2 registers, found {}", n),
+            ReadOnlyDestination(r) => write!(f, "can't write to register {}", r),
+            SensorOutOfRange(r) => write!(f, "sensor {} can't be read in this mode", r),
+            TooManyInstructions(n) => write!(
+                f,
+                "{} instructions, at most {} are allowed",
+                n, MAX_INSTRUCTIONS
+            ),
+            MissingMode => write!(f, "expected WALK or RUN"),
+            TrailingInput => write!(f, "nothing may come after WALK or RUN"),
+            UnexpectedArgument(arg) => write!(f, "WALK and RUN take no arguments, found `{}`", arg),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+}
+
+impl Script {
+    /// Whether the droid jumps given the state of its sensors, where
+    /// bit `i` is set if there's ground `i + 1` tiles ahead.
+    pub fn jumps(&self, sensors: u16) -> bool {
+        let (mut t, mut j) = (false, false);
+        for inst in self.instructions.iter() {
+            let src = match inst.src {
+                Register::T => t,
+                Register::J => j,
+                r => sensors & (1 << (r.distance().unwrap() - 1)) != 0,
+            };
+            let dst = if inst.dst == Register::T {
+                &mut t
+            } else {
+                &mut j
+            };
+            *dst = match inst.op {
+                Op::And => *dst && src,
+                Op::Or => *dst || src,
+                Op::Not => !src,
+            };
+        }
+        j
+    }
+
+    /// Whether the droid running this script makes it across `hull`.
+    pub fn survives(&self, hull: &Hull) -> bool {
+        hull.cross(|sensors| self.jumps(sensors & self.mode.sensor_mask()))
+    }
+}
+
+impl fmt::Display for Script {
+    /// Formats the script exactly as it should be sent to the droid,
+    /// including the final newline.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for inst in self.instructions.iter() {
+            writeln!(f, "{}", inst)?;
+        }
+        match self.mode {
+            Mode::Walk => writeln!(f, "WALK"),
+            Mode::Run => writeln!(f, "RUN"),
+        }
+    }
+}
+
+impl FromStr for Script {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut instructions = Vec::new();
+        let mut mode = None;
+        let mut last_line = 0;
+        for (i, line) in s.lines().enumerate() {
+            let line_num = i + 1;
+            let err = |kind| ParseError {
+                line: line_num,
+                kind,
+            };
+            let words: Vec<_> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            last_line = line_num;
+            if mode.is_some() {
+                return Err(err(ParseErrorKind::TrailingInput));
+            }
+            let op = match words[0] {
+                "WALK" | "RUN" if words.len() > 1 => {
+                    return Err(err(ParseErrorKind::UnexpectedArgument(
+                        words[1].to_string(),
+                    )));
+                }
+                "WALK" | "RUN" => {
+                    mode = Some(if words[0] == "WALK" {
+                        Mode::Walk
+                    } else {
+                        Mode::Run
+                    });
+                    continue;
+                }
+                "AND" => Op::And,
+                "OR" => Op::Or,
+                "NOT" => Op::Not,
+                other => return Err(err(ParseErrorKind::UnknownOp(other.to_string()))),
+            };
+            if words.len() != 3 {
+                return Err(err(ParseErrorKind::WrongArgCount(words.len() - 1)));
+            }
+            let src: Register = words[1].parse().map_err(err)?;
+            let dst: Register = words[2].parse().map_err(err)?;
+            if !dst.is_writable() {
+                return Err(err(ParseErrorKind::ReadOnlyDestination(dst)));
+            }
+            instructions.push((line_num, Instruction::new(op, src, dst)));
+        }
+        let mode = mode.ok_or(ParseError {
+            line: last_line + 1,
+            kind: ParseErrorKind::MissingMode,
+        })?;
+        for (line, inst) in instructions.iter() {
+            if inst.src.distance().unwrap_or(0) > mode.sensor_range() {
+                return Err(ParseError {
+                    line: *line,
+                    kind: ParseErrorKind::SensorOutOfRange(inst.src),
+                });
+            }
+        }
+        if instructions.len() > MAX_INSTRUCTIONS {
+            return Err(ParseError {
+                line: instructions[MAX_INSTRUCTIONS].0,
+                kind: ParseErrorKind::TooManyInstructions(instructions.len()),
+            });
+        }
+        Ok(Script {
+            instructions: instructions.into_iter().map(|(_, inst)| inst).collect(),
+            mode,
+        })
+    }
+}
+
+/// A stretch of hull the droid has to cross, starting from the tile
+/// the droid stands on. Everything past its end is ground.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hull {
+    ground: Vec<bool>,
+}
+
+impl Hull {
+    pub fn len(&self) -> usize {
+        self.ground.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ground.is_empty()
+    }
+
+    pub fn is_ground(&self, pos: usize) -> bool {
+        *self.ground.get(pos).unwrap_or(&true)
+    }
+
+    /// What all nine sensors see from `pos`, with bit `i` set if
+    /// there's ground `i + 1` tiles ahead.
+    pub fn sensors(&self, pos: usize) -> u16 {
+        (0..9)
+            .filter(|i| self.is_ground(pos + i + 1))
+            .fold(0, |acc, i| acc | (1 << i))
+    }
+
+    /// Moves the droid across the hull, asking `jump` whether to jump
+    /// at each step. Jumps land four tiles ahead. Returns whether the
+    /// droid made it past the end without falling into a hole.
+    pub fn cross(&self, mut jump: impl FnMut(u16) -> bool) -> bool {
+        let mut pos = 0;
+        while pos < self.len() {
+            if !self.is_ground(pos) {
+                return false;
+            }
+            pos += if jump(self.sensors(pos)) { 4 } else { 1 };
+        }
+        true
+    }
+
+    /// Finds the hull the droid fell in from its report, that is, the
+    /// bottom row of the first frame after "Didn't make it across".
+    pub fn from_report<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        lines
+            .into_iter()
+            .skip_while(|l| !l.contains("Didn't make it across"))
+            .find(|l| l.contains('#') && l.chars().all(|c| c == '#' || c == '.'))
+            .and_then(|l| l.parse().ok())
+    }
+}
+
+impl FromStr for Hull {
+    type Err = char;
+    /// Parses a hull drawn with `#` for ground and `.` for holes,
+    /// returning the first other char found as an error.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ground = s
+            .trim()
+            .chars()
+            .map(|c| match c {
+                '#' => Ok(true),
+                '.' => Ok(false),
+                c => Err(c),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Hull { ground })
+    }
+}
+
+impl fmt::Display for Hull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s: String = self
+            .ground
+            .iter()
+            .map(|&g| if g { '#' } else { '.' })
+            .collect();
+        write!(f, "{}", s)
+    }
+}
+
+/// Sensors that have to be on or off for the clause to be true. The
+/// clause is an OR of all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Clause {
+    on: u16,
+    off: u16,
+}
+
+impl Clause {
+    fn is_true(self, sensors: u16) -> bool {
+        sensors & self.on != 0 || !sensors & self.off != 0
+    }
+
+    fn len(self) -> usize {
+        (self.on.count_ones() + self.off.count_ones()) as usize
+    }
+
+    fn negated(self) -> usize {
+        self.off.count_ones() as usize
+    }
+
+    /// Instructions needed to add this clause to `J` when it's the
+    /// first one, built directly in `J`.
+    fn first_cost(self) -> usize {
+        // one negated sensor can go first with NOT, any other takes
+        // an extra instruction to negate it in T
+        self.len() + self.negated().saturating_sub(1)
+    }
+
+    /// Instructions needed to build this clause in `T` and AND it
+    /// into `J`, if that's possible at all.
+    fn other_cost(self) -> Option<usize> {
+        match (self.len(), self.negated()) {
+            (1, 0) => Some(1),
+            (1, 1) => Some(2),
+            (len, 0) => Some(len + 2),
+            (len, 1) => Some(len + 1),
+            _ => None,
+        }
+    }
+
+    fn sensors(mask: u16) -> impl Iterator<Item = Register> {
+        (0..9)
+            .filter(move |i| mask & (1 << i) != 0)
+            .map(|i| Register::SENSORS[i])
+    }
+
+    fn compile_first(self, out: &mut Vec<Instruction>) {
+        use Register::*;
+        let mut negated = Clause::sensors(self.off);
+        if let Some(r) = negated.next() {
+            out.push(Instruction::new(Op::Not, r, J));
+        }
+        for r in negated {
+            out.push(Instruction::new(Op::Not, r, T));
+            out.push(Instruction::new(Op::Or, T, J));
+        }
+        // J starts out false, so OR works even if this is the very
+        // first instruction
+        for r in Clause::sensors(self.on) {
+            out.push(Instruction::new(Op::Or, r, J));
+        }
+    }
+
+    fn compile_other(self, out: &mut Vec<Instruction>) {
+        use Register::*;
+        if self.len() == 1 && self.negated() == 0 {
+            let r = Clause::sensors(self.on).next().unwrap();
+            out.push(Instruction::new(Op::And, r, J));
+            return;
+        }
+        let mut on = Clause::sensors(self.on);
+        match Clause::sensors(self.off).next() {
+            Some(r) => out.push(Instruction::new(Op::Not, r, T)),
+            None => {
+                let r = on.next().unwrap();
+                out.push(Instruction::new(Op::Not, r, T));
+                out.push(Instruction::new(Op::Not, T, T));
+            }
+        }
+        for r in on {
+            out.push(Instruction::new(Op::Or, r, T));
+        }
+        out.push(Instruction::new(Op::And, T, J));
+    }
+}
+
+fn compile(clauses: &[Clause], mode: Mode) -> Script {
+    let mut instructions = Vec::new();
+    match clauses.split_first() {
+        Some((first, rest)) => {
+            first.compile_first(&mut instructions);
+            for clause in rest {
+                clause.compile_other(&mut instructions);
+            }
+        }
+        // no clauses means always jumping. T always starts out false
+        None => instructions.push(Instruction::new(Op::Not, Register::T, Register::J)),
+    }
+    Script { instructions, mode }
+}
+
+/// Orders clauses so the one gaining the most from being built in `J`
+/// goes first, returning them with the total instruction count.
+fn arrange(mut clauses: Vec<Clause>) -> Option<(usize, Vec<Clause>)> {
+    if clauses.is_empty() {
+        return Some((1, clauses));
+    }
+    let saving = |c: &Clause| c.other_cost().map(|o| o - c.first_cost());
+    // clauses that can only go first sort before all others
+    let first = (0..clauses.len()).max_by_key(|&i| saving(&clauses[i]).unwrap_or(usize::MAX))?;
+    clauses.swap(0, first);
+    let mut cost = clauses[0].first_cost();
+    for c in clauses[1..].iter() {
+        cost += c.other_cost()?;
+    }
+    Some((cost, clauses))
+}
+
+/// Readings where the droid has to walk or jump to make it across.
+struct Table {
+    walk: Vec<u16>,
+    jump: Vec<u16>,
+}
+
+struct Search<'a> {
+    hulls: &'a [Hull],
+    mode: Mode,
+    decisions: HashMap<u16, bool>,
+    tables_left: usize,
+    best: Option<(usize, Vec<Clause>)>,
+    candidates: Vec<Clause>,
+}
+
+impl<'a> Search<'a> {
+    /// Moves the droid over the hulls starting from hull `h` at `pos`,
+    /// trying both choices whenever there's a reading with no decision
+    /// yet.
+    fn walk_hulls(&mut self, mut h: usize, mut pos: usize) {
+        loop {
+            if self.tables_left == 0 {
+                return;
+            }
+            if h == self.hulls.len() {
+                self.tables_left -= 1;
+                self.solve_table();
+                return;
+            }
+            let hull = &self.hulls[h];
+            if pos >= hull.len() {
+                h += 1;
+                pos = 0;
+                continue;
+            }
+            if !hull.is_ground(pos) {
+                return;
+            }
+            let sensors = hull.sensors(pos) & self.mode.sensor_mask();
+            match self.decisions.get(&sensors) {
+                Some(&jump) => pos += if jump { 4 } else { 1 },
+                None => {
+                    for &jump in [false, true].iter() {
+                        self.decisions.insert(sensors, jump);
+                        self.walk_hulls(h, pos);
+                    }
+                    self.decisions.remove(&sensors);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn solve_table(&mut self) {
+        let mut table = Table {
+            walk: Vec::new(),
+            jump: Vec::new(),
+        };
+        for (&sensors, &jump) in self.decisions.iter() {
+            if jump {
+                table.jump.push(sensors);
+            } else {
+                table.walk.push(sensors);
+            }
+        }
+        // each walk reading is a bit in the cover masks
+        if table.walk.len() > 128 {
+            return;
+        }
+        // clauses that are true wherever the droid must jump, along
+        // with the walk readings they make false
+        let mut usable: Vec<(u128, Clause)> = Vec::new();
+        for &clause in self.candidates.iter() {
+            if !table.jump.iter().all(|&s| clause.is_true(s)) {
+                continue;
+            }
+            let covers = table
+                .walk
+                .iter()
+                .enumerate()
+                .filter(|(_, &s)| !clause.is_true(s))
+                .fold(0u128, |acc, (i, _)| acc | (1 << i));
+            if covers != 0 {
+                usable.push((covers, clause));
+            }
+        }
+        usable.sort_by_key(|&(_, c)| (c.first_cost(), c.other_cost().unwrap_or(usize::MAX)));
+        // drop clauses that cost at least as much as another one making
+        // the same readings false, or more
+        let mut kept: Vec<(u128, Clause)> = Vec::new();
+        for &(covers, clause) in usable.iter() {
+            let dominated = kept.iter().any(|&(k, c)| {
+                covers & !k == 0
+                    && c.other_cost().unwrap_or(usize::MAX)
+                        <= clause.other_cost().unwrap_or(usize::MAX)
+            });
+            if !dominated {
+                kept.push((covers, clause));
+            }
+        }
+        let all = if table.walk.len() == 128 {
+            u128::MAX
+        } else {
+            (1 << table.walk.len()) - 1
+        };
+        let mut chosen = Vec::new();
+        self.cover(all, &kept, &mut chosen);
+    }
+
+    /// Exhaustive set cover with branch and bound. Each step branches
+    /// on the clauses covering the reading with the fewest options.
+    fn cover(&mut self, uncovered: u128, usable: &[(u128, Clause)], chosen: &mut Vec<Clause>) {
+        let limit = match &self.best {
+            Some((cost, _)) => *cost,
+            None => MAX_INSTRUCTIONS + 1,
+        };
+        if uncovered == 0 {
+            if let Some((cost, clauses)) = arrange(chosen.clone()) {
+                if cost < limit {
+                    self.best = Some((cost, clauses));
+                }
+            }
+            return;
+        }
+        let options = |bit: u32| usable.iter().filter(|(c, _)| c & (1 << bit) != 0).count();
+        let target = (0..128)
+            .filter(|&bit| uncovered & (1 << bit) != 0)
+            .min_by_key(|&bit| options(bit))
+            .unwrap();
+        for &(covers, clause) in usable.iter() {
+            if covers & (1 << target) == 0 {
+                continue;
+            }
+            let rest = uncovered & !covers;
+            chosen.push(clause);
+            // adding clauses never makes the script shorter, and each
+            // one takes at least another instruction
+            let needed = if rest == 0 { 0 } else { 1 };
+            if arrange(chosen.clone()).is_some_and(|(cost, _)| cost + needed < limit) {
+                self.cover(rest, usable, chosen);
+            }
+            chosen.pop();
+        }
+    }
+}
+
+/// Every clause of up to `MAX_CLAUSE_LEN` sensors readable in `mode`.
+fn candidate_clauses(mode: Mode) -> Vec<Clause> {
+    let n = mode.sensor_range();
+    let mut clauses = Vec::new();
+    // each sensor is either unused, used as is, or negated
+    let mut digits = vec![0u8; n];
+    loop {
+        let mut clause = Clause { on: 0, off: 0 };
+        for (i, &d) in digits.iter().enumerate() {
+            match d {
+                1 => clause.on |= 1 << i,
+                2 => clause.off |= 1 << i,
+                _ => (),
+            }
+        }
+        if (1..=MAX_CLAUSE_LEN).contains(&clause.len()) {
+            clauses.push(clause);
+        }
+        // next combination, counting in base 3
+        match digits.iter().position(|&d| d < 2) {
+            Some(i) => {
+                digits[i] += 1;
+                digits[..i].iter_mut().for_each(|d| *d = 0);
+            }
+            None => return clauses,
+        }
+    }
+}
+
+/// Searches for the shortest script it can find that gets the droid
+/// across all `hulls`. Returns `None` if there's no such script within
+/// the instruction limit, but also if the search runs out of decision
+/// tables to try (see `MAX_TABLES`) before finding one.
+pub fn search(hulls: &[Hull], mode: Mode) -> Option<Script> {
+    let mut search = Search {
+        hulls,
+        mode,
+        decisions: HashMap::new(),
+        tables_left: MAX_TABLES,
+        best: None,
+        candidates: candidate_clauses(mode),
+    };
+    search.walk_hulls(0, 0);
+    search.best.map(|(_, clauses)| compile(&clauses, mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "NOT A J\nNOT B T\nOR T J\nNOT C T\nOR T J\nAND D J\nWALK\n";
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let script: Script = SCRIPT.parse().unwrap();
+        assert_eq!(6, script.instructions.len());
+        assert_eq!(Mode::Walk, script.mode);
+        assert_eq!(SCRIPT, script.to_string());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s: &str| s.parse::<Script>().unwrap_err();
+        assert_eq!(
+            ParseErrorKind::UnknownOp("XOR".to_string()),
+            err("NOT A J\nXOR A J\nWALK").kind
+        );
+        assert_eq!(2, err("NOT A J\nXOR A J\nWALK").line);
+        assert_eq!(
+            ParseErrorKind::ReadOnlyDestination(Register::B),
+            err("OR A B\nRUN").kind
+        );
+        assert_eq!(
+            ParseErrorKind::SensorOutOfRange(Register::E),
+            err("OR E J\nWALK").kind
+        );
+        assert_eq!(ParseErrorKind::MissingMode, err("OR A J").kind);
+        assert_eq!(
+            ParseErrorKind::UnexpectedArgument("x".to_string()),
+            err("OR A J\nWALK x").kind
+        );
+        assert_eq!(2, err("OR A J\nWALK x").line);
+        assert_eq!(
+            "line 1: WALK and RUN take no arguments, found `A`",
+            err("RUN A J").to_string()
+        );
+    }
+
+    #[test]
+    fn test_simulation() {
+        let script: Script = SCRIPT.parse().unwrap();
+        assert!(script.survives(&"#####.###########".parse().unwrap()));
+        assert!(script.survives(&"#####..#.########".parse().unwrap()));
+        // needs to jump again right after landing, which it can't see
+        // coming when walking
+        assert!(!script.survives(&"#####.#.##..#####".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_search() {
+        let hulls: Vec<Hull> = [
+            "#####.###########",
+            "#####..#.########",
+            "#####...#########",
+        ]
+        .iter()
+        .map(|h| h.parse().unwrap())
+        .collect();
+        let script = search(&hulls, Mode::Walk).unwrap();
+        assert!(script.instructions.len() <= MAX_INSTRUCTIONS);
+        assert!(hulls.iter().all(|h| script.survives(h)));
+    }
+}
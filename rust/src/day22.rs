@@ -1,176 +1,327 @@
-#[derive(Debug)]
-enum ShuffleTech {
-    DealNew,
-    CutN(i64),
-    DealIncr(u64),
-}
+pub mod shuffle;
+
+use crate::util::num;
+use shuffle::ShuffleTech;
 
-struct Shuffler {
-    stack: Vec<u64>,
-    working_table: Vec<u64>,
+/// A shuffle of a deck of `size` cards, as the function taking the
+/// position of each card before it to its position after it. Every
+/// technique is a linear function `a * x + b` mod `size`, so any
+/// sequence of them is one too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinearShuffle {
+    a: i128,
+    b: i128,
+    size: i128,
 }
 
-impl Shuffler {
-    fn new(stack: impl Into<Vec<u64>>) -> Self {
-        let stack = stack.into();
-        let working_table = Vec::with_capacity(stack.len());
-        Self {
-            stack,
-            working_table,
+impl LinearShuffle {
+    /// Leaves every card where it is.
+    pub fn identity(size: i128) -> Self {
+        LinearShuffle { a: 1, b: 0, size }
+    }
+
+    pub fn from_technique(technique: &ShuffleTech, size: i128) -> Self {
+        let (a, b) = match *technique {
+            // the top card ends up at the bottom, at -1
+            ShuffleTech::DealNew => (-1, -1),
+            ShuffleTech::CutN(n) => (1, -(n as i128)),
+            ShuffleTech::DealIncr(n) => (n as i128, 0),
+        };
+        LinearShuffle {
+            a: a.rem_euclid(size),
+            b: b.rem_euclid(size),
+            size,
         }
     }
 
-    fn shuffle_with(&mut self, technique: &ShuffleTech) {
-        match *technique {
-            ShuffleTech::DealNew => {
-                self.stack.reverse();
-            }
-            ShuffleTech::CutN(n) => {
-                if n >= 0 {
-                    self.stack.rotate_left(n as usize);
-                } else {
-                    self.stack.rotate_right(n.abs() as usize);
-                }
-            }
-            ShuffleTech::DealIncr(n) => {
-                let len = self.stack.len();
-                self.working_table.clear();
-                self.working_table.resize(len, 0);
-                let cards = self.stack.drain(..);
-                for (i, c) in (0..).step_by(n as usize).take(len).zip(cards) {
-                    self.working_table[i % len] = c;
-                }
-                std::mem::swap(&mut self.stack, &mut self.working_table);
+    /// The whole process, applying each technique in order.
+    pub fn from_techniques(techniques: &[ShuffleTech], size: i128) -> Self {
+        techniques
+            .iter()
+            .map(|t| LinearShuffle::from_technique(t, size))
+            .fold(LinearShuffle::identity(size), |acc, t| acc * t)
+    }
+
+    /// Repeats the shuffle `n` times, by squaring.
+    pub fn pow(self, mut n: u128) -> Self {
+        let mut base = self;
+        let mut r = LinearShuffle::identity(self.size);
+        while n > 0 {
+            if n % 2 == 1 {
+                r = r * base;
             }
+            base = base * base;
+            n >>= 1;
         }
+        r
+    }
+
+    /// The shuffle that puts the cards back in their original order,
+    /// if there's one. Dealing with an increment that's not coprime
+    /// with the deck size would put several cards in the same spot, so
+    /// it can't be undone.
+    pub fn inverse(self) -> Option<Self> {
+        let a = num::mod_inverse(self.a, self.size)?;
+        Some(LinearShuffle {
+            a,
+            b: num::mulmod(-self.b, a, self.size),
+            size: self.size,
+        })
+    }
+
+    /// Where the card that started at `position` ends up, which for
+    /// a factory order deck is also where card number `position` ends up.
+    pub fn position_of(&self, position: i128) -> i128 {
+        (num::mulmod(self.a, position, self.size) + self.b) % self.size
     }
 
-    fn shuffle_seq(&mut self, shuffles: &[ShuffleTech]) {
-        for shuffle in shuffles.iter() {
-            self.shuffle_with(shuffle);
+    /// Which card ends up at `position`, counting cards by where they
+    /// started.
+    pub fn card_at(&self, position: i128) -> i128 {
+        self.inverse()
+            .expect("Shuffle loses cards")
+            .position_of(position)
+    }
+
+    /// The shortest process doing this shuffle: dealing into a new
+    /// stack, dealing with an increment and cutting, in that order,
+    /// leaving out whatever isn't needed. Dealing into a new stack is
+    /// only used when it makes the increment smaller, and cuts are
+    /// taken from whichever end of the deck is closer.
+    pub fn to_techniques(&self) -> Vec<ShuffleTech> {
+        // dealing into a new stack and then with increment `k` is
+        // `-k * x - k`, and cutting `n` after either subtracts `n`
+        let reverse = self.size - self.a < self.a;
+        let (increment, start) = if reverse {
+            (self.size - self.a, self.a)
+        } else {
+            (self.a, 0)
+        };
+        let mut cut = (start - self.b).rem_euclid(self.size);
+        if cut > self.size / 2 {
+            cut -= self.size;
+        }
+        let mut techniques = Vec::new();
+        if reverse {
+            techniques.push(ShuffleTech::DealNew);
+        }
+        if increment != 1 {
+            techniques.push(ShuffleTech::DealIncr(increment as u64));
         }
+        if cut != 0 {
+            techniques.push(ShuffleTech::CutN(cut as i64));
+        }
+        techniques
     }
 }
 
-fn parse_shuffle(input: &str) -> Vec<ShuffleTech> {
-    input
-        .trim()
-        .lines()
-        .map(|l| {
-            let mut words = l.trim().split(" ");
-            // the second word is enough to distinguish
-            let second_word = words.nth(1).unwrap();
-            match second_word {
-                "into" => ShuffleTech::DealNew,
-                "with" => {
-                    let n = words.last().unwrap().parse().expect("Invalid deal incr");
-                    ShuffleTech::DealIncr(n)
-                }
-                // any other is expected to be a number for the Cut
-                // technique. If that's not the case it's an unknown
-                // technique and panicking is appropriate.
-                n => ShuffleTech::CutN(n.parse().expect("Invalid cut N")),
-            }
-        })
-        .collect()
+/// Composes two shuffles: `first * second` is doing `first` and then
+/// `second` on the result.
+impl std::ops::Mul for LinearShuffle {
+    type Output = Self;
+    fn mul(self, second: Self) -> Self {
+        assert_eq!(self.size, second.size, "Shuffles are for different decks");
+        LinearShuffle {
+            a: num::mulmod(second.a, self.a, self.size),
+            b: second.position_of(self.b),
+            size: self.size,
+        }
+    }
 }
 
-pub fn part1(input: &str) -> usize {
-    let shuffles = parse_shuffle(input);
-    let stack: Vec<_> = (0..10007).collect();
-    let mut shuffler = Shuffler::new(stack);
-    shuffler.shuffle_seq(&shuffles);
-    shuffler.stack.iter().position(|c| *c == 2019).unwrap()
+/// Rewrites a process for a deck of `size` cards in its shortest form,
+/// with at most three techniques.
+pub fn simplify(techniques: &[ShuffleTech], size: u64) -> Vec<ShuffleTech> {
+    LinearShuffle::from_techniques(techniques, size as i128).to_techniques()
 }
 
-struct Deck {
-    start: i128,
-    increment: i128,
-    size: i128,
+pub fn part1(input: &str) -> i128 {
+    const SIZE: u64 = 10007;
+    let shuffles = shuffle::parse(input, SIZE).unwrap_or_else(|e| panic!("{}", e));
+    LinearShuffle::from_techniques(&shuffles, SIZE as i128).position_of(2019)
 }
 
-impl Deck {
-    fn nth(&self, n: i128) -> i128 {
-        // because the operations that modify these values
-        // all use `rem_euclid`, this is guaranteed to be positive
-        // and the correct card, even using the default % operator
-        (self.start + self.increment * n) % self.size
-    }
-    /// Adds start offset to given `x`, maintaining it within deck size
-    fn add_offset(&mut self, x: i128) {
-        self.start += x;
-        self.start = self.start.rem_euclid(self.size);
-    }
-    /// Multiplies increment by a given x maintaining it within deck size`
-    fn mul_increment(&mut self, x: i128) {
-        self.increment *= x;
-        self.increment = self.increment.rem_euclid(self.size);
-    }
-
-    fn shuffle_with(mut self, shuffle: &ShuffleTech) -> Deck {
-        use ShuffleTech::*;
-        match *shuffle {
-            DealNew => {
-                // This works because when the offset is inverted
-                // and the modulo is taken, it'll be equal to the last
-                // number of the list.
-                self.mul_increment(-1);
-                self.add_offset(self.increment);
+pub fn part2(input: &str) -> i128 {
+    const SIZE: u64 = 119_315_717_514_047;
+    const REPS: u128 = 101_741_582_076_661;
+    let shuffles = shuffle::parse(input, SIZE).unwrap_or_else(|e| panic!("{}", e));
+    LinearShuffle::from_techniques(&shuffles, SIZE as i128)
+        .pow(REPS)
+        .card_at(2020)
+}
+
+impl_solution!(Day22, 22);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/22-1.txt");
+
+    /// Shuffles by actually moving cards around, to check the linear
+    /// shuffles against.
+    struct Shuffler {
+        stack: Vec<u64>,
+        working_table: Vec<u64>,
+    }
+
+    impl Shuffler {
+        fn new(stack: impl Into<Vec<u64>>) -> Self {
+            let stack = stack.into();
+            let working_table = Vec::with_capacity(stack.len());
+            Self {
+                stack,
+                working_table,
             }
-            DealIncr(n) => {
-                let inv_mod = crate::util::modular_inverse(n as i64, self.size as i64) as i128;
-                self.mul_increment(inv_mod);
+        }
+
+        fn shuffle_with(&mut self, technique: &ShuffleTech) {
+            match *technique {
+                ShuffleTech::DealNew => {
+                    self.stack.reverse();
+                }
+                ShuffleTech::CutN(n) => {
+                    if n >= 0 {
+                        self.stack.rotate_left(n as usize);
+                    } else {
+                        self.stack.rotate_right(n.unsigned_abs() as usize);
+                    }
+                }
+                ShuffleTech::DealIncr(n) => {
+                    let len = self.stack.len();
+                    self.working_table.clear();
+                    self.working_table.resize(len, 0);
+                    let cards = self.stack.drain(..);
+                    for (i, c) in (0..).step_by(n as usize).take(len).zip(cards) {
+                        self.working_table[i % len] = c;
+                    }
+                    std::mem::swap(&mut self.stack, &mut self.working_table);
+                }
+            }
+        }
+
+        fn shuffle_seq(&mut self, shuffles: &[ShuffleTech]) {
+            for shuffle in shuffles.iter() {
+                self.shuffle_with(shuffle);
+            }
+        }
+    }
+
+    fn shuffled(techniques: &[ShuffleTech], size: u64) -> Vec<u64> {
+        let mut shuffler = Shuffler::new((0..size).collect::<Vec<_>>());
+        shuffler.shuffle_seq(techniques);
+        shuffler.stack
+    }
+
+    /// A small xorshift generator, so the random processes are the same
+    /// on every run.
+    fn random_techniques(seed: &mut u64, size: u64, len: usize) -> Vec<ShuffleTech> {
+        let mut next = || {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            *seed
+        };
+        (0..len)
+            .map(|_| match next() % 3 {
+                0 => ShuffleTech::DealNew,
+                1 => ShuffleTech::CutN((next() % (2 * size)) as i64 - size as i64),
+                _ => loop {
+                    let n = next() % size;
+                    if num::gcd_u64(n, size) == 1 {
+                        break ShuffleTech::DealIncr(n);
+                    }
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_shuffler() {
+        let mut seed = 0x2019_1222;
+        for &size in [10, 11, 12, 53, 100].iter() {
+            for len in 0..20 {
+                let techniques = random_techniques(&mut seed, size, len);
+                let deck = shuffled(&techniques, size);
+                let shuffle = LinearShuffle::from_techniques(&techniques, size as i128);
+                for (pos, &card) in deck.iter().enumerate() {
+                    assert_eq!(card as i128, shuffle.card_at(pos as i128));
+                    assert_eq!(pos as i128, shuffle.position_of(card as i128));
+                }
             }
-            CutN(n) => self.start = self.nth(n as i128),
         }
-        self
     }
-    fn shuffle_seq_n(mut self, shuffles: &[ShuffleTech], n: u64) -> Deck {
-        // apply shuffle once
-        for shuffle in shuffles.iter() {
-            self = self.shuffle_with(shuffle);
+
+    #[test]
+    fn test_algebra() {
+        let mut seed = 0xdeca;
+        for &size in [10, 37].iter() {
+            let id = LinearShuffle::identity(size as i128);
+            for len in 1..10 {
+                let techniques = random_techniques(&mut seed, size, len);
+                let f = LinearShuffle::from_techniques(&techniques, size as i128);
+                let g = LinearShuffle::from_techniques(&techniques[1..], size as i128);
+                let first = LinearShuffle::from_technique(&techniques[0], size as i128);
+                assert_eq!(f, first * g);
+                assert_eq!(id, f * f.inverse().unwrap());
+                assert_eq!(id, f.inverse().unwrap() * f);
+                assert_eq!(f * f * f, f.pow(3));
+                assert_eq!(id, f.pow(0));
+
+                let repeated: Vec<_> = techniques.iter().cycle().take(5 * len).cloned().collect();
+                let deck = shuffled(&repeated, size);
+                let f5 = f.pow(5);
+                for (pos, &card) in deck.iter().enumerate() {
+                    assert_eq!(card as i128, f5.card_at(pos as i128));
+                }
+            }
         }
-        // repeating this process n times means the final increment will
-        // be increment ^ n mod size
-        let final_inc = modular_exp(self.increment, n as i128, self.size);
-        // we'll use this in the following calc
-        // the final start offset is a geometric series
-        // final_start = start * (1 + increment + increment^2 + ... increment^n-1)
-        //
-        // so final_start = start * (1-increment^n) * (1-increment)^-1
-        // note that increment^n is final_inc, (1-increment)^-1 is the
-        // modular inverse
-        let inverse =
-            crate::util::modular_inverse(1 - self.increment as i64, self.size as i64) as i128;
-        let final_start = self.start * (((1 - final_inc) * inverse) % self.size);
-        self.start = final_start.rem_euclid(self.size);
-        self.increment = final_inc;
-        self
+        // no way to tell apart the cards that land on the same spot
+        let lossy = LinearShuffle::from_technique(&ShuffleTech::DealIncr(4), 10);
+        assert_eq!(None, lossy.inverse());
     }
-}
 
-fn modular_exp(mut base: i128, mut exp: i128, m: i128) -> i128 {
-    let mut r = 1;
-    base = base % m;
-    while exp > 0 {
-        if exp % 2 == 1 {
-            r = (r * base) % m;
+    #[test]
+    fn test_simplify() {
+        let mut seed = 0x5eed;
+        for &size in [10, 11, 53].iter() {
+            for len in 0..30 {
+                let techniques = random_techniques(&mut seed, size, len);
+                let simple = simplify(&techniques, size);
+                assert!(simple.len() <= 3);
+                assert_eq!(shuffled(&techniques, size), shuffled(&simple, size));
+                assert_eq!(simple, simplify(&simple, size));
+            }
         }
-        exp >>= 1;
-        base = base.pow(2) % m;
+        assert_eq!(
+            Vec::<ShuffleTech>::new(),
+            simplify(&[ShuffleTech::DealNew; 2], 10)
+        );
+        assert_eq!(
+            vec![ShuffleTech::DealNew, ShuffleTech::CutN(-1)],
+            simplify(
+                &[
+                    ShuffleTech::CutN(3),
+                    ShuffleTech::DealNew,
+                    ShuffleTech::CutN(2)
+                ],
+                10
+            )
+        );
     }
-    r
-}
 
-pub fn part2(input: &str) -> i128 {
-    const SIZE: i128 = 119_315_717_514_047;
-    const REPS: u64 = 101_741_582_076_661;
-    let shuffles = parse_shuffle(input);
-    let deck = Deck {
-        start: 0,
-        increment: 1,
-        size: SIZE,
-    };
-    let deck = deck.shuffle_seq_n(&shuffles, REPS);
-    deck.nth(2020)
+    #[test]
+    fn test_answers() {
+        assert_eq!(part1(INPUT), 8326);
+        let techniques = shuffle::parse(INPUT, 10007).unwrap();
+        assert_eq!(
+            LinearShuffle::from_techniques(&techniques, 10007).card_at(8326),
+            2019
+        );
+        let simple = simplify(&techniques, 10007);
+        assert_eq!(
+            LinearShuffle::from_techniques(&simple, 10007).position_of(2019),
+            8326
+        );
+    }
 }
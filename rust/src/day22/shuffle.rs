@@ -0,0 +1,262 @@
+//! The space cards' shuffle process: a list of techniques, one per
+//! line, like
+//!
+//! ```text
+//! deal with increment 7
+//! cut -2
+//! deal into new stack
+//! ```
+
+use std::fmt;
+
+use crate::util::num;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShuffleTech {
+    DealNew,
+    CutN(i64),
+    DealIncr(u64),
+}
+
+impl fmt::Display for ShuffleTech {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShuffleTech::DealNew => write!(f, "deal into new stack"),
+            ShuffleTech::CutN(n) => write!(f, "cut {}", n),
+            ShuffleTech::DealIncr(n) => write!(f, "deal with increment {}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A word that doesn't fit any technique.
+    UnexpectedWord(String),
+    /// The line ends before the technique does.
+    UnexpectedEnd,
+    InvalidNumber(String),
+    ZeroIncrement,
+    /// Dealing with this increment would put several cards in the same
+    /// spot.
+    IncrementNotCoprime {
+        increment: u64,
+        size: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line the error was found in.
+    pub line: usize,
+    /// 1-based column of the offending word, or just past the end of
+    /// the line if it's cut short.
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ParseErrorKind::*;
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            UnexpectedWord(w) => write!(f, "unexpected `{}`", w),
+            UnexpectedEnd => write!(f, "unexpected end of line"),
+            InvalidNumber(n) => write!(f, "`{}` is not a valid number", n),
+            ZeroIncrement => write!(f, "can't deal with increment 0"),
+            IncrementNotCoprime { increment, size } => write!(
+                f,
+                "increment {} shares a factor with the deck size {}",
+                increment, size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The fixed words each technique starts with, and whether a number
+/// follows them.
+const TECHNIQUES: [(&[&str], bool); 3] = [
+    (&["deal", "into", "new", "stack"], false),
+    (&["cut"], true),
+    (&["deal", "with", "increment"], true),
+];
+
+/// Splits a line into words along with the 1-based column each starts
+/// at.
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let ends = std::iter::once((line.len(), ' '));
+    for (i, c) in line.char_indices().chain(ends) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((line[..s].chars().count() + 1, &line[s..i]));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    words
+}
+
+/// Parses one technique from its words, returning the index of the
+/// offending word along with the error. An index past the last word
+/// means the line ended too soon.
+fn parse_technique(words: &[&str], size: u64) -> Result<ShuffleTech, (usize, ParseErrorKind)> {
+    let unexpected = |i: usize| match words.get(i) {
+        Some(w) => (i, ParseErrorKind::UnexpectedWord(w.to_string())),
+        None => (i, ParseErrorKind::UnexpectedEnd),
+    };
+    // the error is reported at the furthest point any technique matched
+    let mut furthest = 0;
+    let (keywords, has_number) = TECHNIQUES
+        .iter()
+        .copied()
+        .find(|(keywords, _)| {
+            let matched = keywords
+                .iter()
+                .zip(words)
+                .take_while(|(k, w)| k == w)
+                .count();
+            furthest = furthest.max(matched);
+            matched == keywords.len()
+        })
+        .ok_or_else(|| unexpected(furthest))?;
+
+    let len = keywords.len() + has_number as usize;
+    if len < words.len() {
+        return Err(unexpected(len));
+    }
+    if !has_number {
+        return Ok(ShuffleTech::DealNew);
+    }
+    let number = *words.get(len - 1).ok_or_else(|| unexpected(len - 1))?;
+    let invalid = || (len - 1, ParseErrorKind::InvalidNumber(number.to_string()));
+    if keywords[0] == "cut" {
+        return number.parse().map(ShuffleTech::CutN).map_err(|_| invalid());
+    }
+    let increment: u64 = number.parse().map_err(|_| invalid())?;
+    if increment == 0 {
+        Err((len - 1, ParseErrorKind::ZeroIncrement))
+    } else if num::gcd_u64(increment, size) != 1 {
+        Err((
+            len - 1,
+            ParseErrorKind::IncrementNotCoprime { increment, size },
+        ))
+    } else {
+        Ok(ShuffleTech::DealIncr(increment))
+    }
+}
+
+/// Parses a shuffle process for a deck of `size` cards. Blank lines
+/// are skipped.
+pub fn parse(input: &str, size: u64) -> Result<Vec<ShuffleTech>, ParseError> {
+    let mut techniques = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let words = words(line);
+        if words.is_empty() {
+            continue;
+        }
+        let texts: Vec<_> = words.iter().map(|&(_, w)| w).collect();
+        let technique = parse_technique(&texts, size).map_err(|(word, kind)| {
+            let column = match words.get(word) {
+                Some(&(column, _)) => column,
+                None => line.trim_end().chars().count() + 1,
+            };
+            ParseError {
+                line: i + 1,
+                column,
+                kind,
+            }
+        })?;
+        techniques.push(technique);
+    }
+    Ok(techniques)
+}
+
+/// Writes the process back as text, one technique per line.
+pub fn format(techniques: &[ShuffleTech]) -> String {
+    techniques.iter().map(|t| format!("{}\n", t)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../../input/22-1.txt");
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let techniques = parse(INPUT, 10007).unwrap();
+        assert_eq!(100, techniques.len());
+        assert_eq!(techniques, parse(&format(&techniques), 10007).unwrap());
+
+        let process = "deal with increment 7\n\tcut  -2\n\ndeal into new stack  \n";
+        assert_eq!(
+            vec![
+                ShuffleTech::DealIncr(7),
+                ShuffleTech::CutN(-2),
+                ShuffleTech::DealNew
+            ],
+            parse(process, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s: &str, size| {
+            let e = parse(s, size).unwrap_err();
+            (e.line, e.column, e.kind)
+        };
+        use ParseErrorKind::*;
+        assert_eq!(
+            (1, 6, UnexpectedWord("onto".to_string())),
+            err("deal onto new stack", 10)
+        );
+        assert_eq!(
+            (2, 1, UnexpectedWord("shuffle".to_string())),
+            err("cut 1\nshuffle", 10)
+        );
+        assert_eq!((1, 20, UnexpectedEnd), err("deal with increment", 10));
+        assert_eq!(
+            (1, 21, UnexpectedWord("3".to_string())),
+            err("deal into new stack 3", 10)
+        );
+        assert_eq!((1, 7, InvalidNumber("x2".to_string())), err("  cut x2", 10));
+        assert_eq!(
+            (3, 21, ZeroIncrement),
+            err("cut 1\n\ndeal with increment 0", 10)
+        );
+        assert_eq!(
+            (
+                1,
+                21,
+                IncrementNotCoprime {
+                    increment: 4,
+                    size: 10
+                }
+            ),
+            err("deal with increment 4", 10)
+        );
+        assert!(parse("deal with increment 4", 10007).is_ok());
+        // sizes past i64::MAX are checked as they are
+        assert_eq!(
+            (
+                1,
+                21,
+                IncrementNotCoprime {
+                    increment: 3,
+                    size: u64::MAX
+                }
+            ),
+            err("deal with increment 3", u64::MAX)
+        );
+        assert!(parse("deal with increment 3", u64::MAX - 2).is_ok());
+        assert_eq!(
+            "line 1, column 5: `1.5` is not a valid number",
+            parse("cut 1.5", 10).unwrap_err().to_string()
+        );
+    }
+}
@@ -128,3 +128,5 @@ pub fn part2(input: &str) -> i64 {
     let mut network = Network::new(input);
     network.run(false).y
 }
+
+impl_solution!(Day23, 23);
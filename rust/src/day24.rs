@@ -1,247 +1,174 @@
-use bitvec::prelude::*;
-use std::collections::{HashMap, HashSet};
-
-type Layout = BitVec<LittleEndian, u32>;
-
-fn apply_sim(grid: &mut Layout, neighbor_counts: &[usize], work_buffer: &mut Layout) -> u32 {
-    for i in 0..25 {
-        let bug = grid[i];
-        let new = if bug && neighbor_counts[i] != 1 {
-            false
-        } else if !bug && (neighbor_counts[i] == 1 || neighbor_counts[i] == 2) {
-            true
-        } else {
-            bug
-        };
-        work_buffer.set(i, new);
-    }
-    grid.clear();
-    grid.extend(work_buffer.drain(..));
-    // this is safe as we know the memory is allocated and we also
-    // won't be reading from it until this same range is
-    // overwritten
-    unsafe { work_buffer.set_len(25) };
-    grid.load().unwrap()
+use std::collections::{BTreeMap, HashSet};
+
+use crate::util::{Direction, Grid, Point};
+
+type Layout = Grid<bool>;
+
+const SIZE: usize = 5;
+const CENTER: Point = Point { x: 2, y: 2 };
+
+fn parse_layout(input: &str) -> Layout {
+    let grid = Grid::parse(input.trim(), |c| c == '#');
+    assert!(
+        grid.width() == SIZE && grid.height() == SIZE,
+        "Layout must be {}x{}",
+        SIZE,
+        SIZE
+    );
+    grid
 }
 
-fn calc_neighbor_counts(grid: &Layout) -> Vec<usize> {
-    let mut counts = Vec::with_capacity(25);
-    for i in 0..25 {
-        let (x, y) = (i % 5, i / 5);
-        let mut count = 0;
-        // doing manual bounds checking to avoid underflow or having to
-        // use signed integers
-        if y > 0 && grid[i - 5] {
-            count += 1;
-        }
-        if y < 4 && grid[i + 5] {
-            count += 1;
-        }
-        if x > 0 && grid[i - 1] {
-            count += 1;
-        }
-        if x < 4 && grid[i + 1] {
-            count += 1;
-        }
-        counts.push(count)
-    }
-    counts
+/// Whether a tile has a bug after a minute, given whether it has one
+/// now and how many of its neighbors do.
+fn next_state(bug: bool, neighbors: usize) -> bool {
+    matches!((bug, neighbors), (true, 1) | (false, 1) | (false, 2))
 }
 
-pub fn part1(input: &str) -> u32 {
-    let mut grid = BitVec::<_, _>::with_capacity(25);
-    for c in input.chars().filter(|c| *c != '\r' && *c != '\n') {
-        grid.push(c == '#');
-    }
+fn biodiversity(grid: &Layout) -> u32 {
+    grid.cells()
+        .iter()
+        .enumerate()
+        .filter(|(_, &bug)| bug)
+        .map(|(i, _)| 1 << i)
+        .sum()
+}
 
-    let mut seen = HashSet::<u32>::new();
-    seen.insert(grid.load().unwrap());
-    let mut work_buffer = bitvec![LittleEndian, u32; 0; 25];
-    loop {
-        let counts = calc_neighbor_counts(&grid);
-        let rating = apply_sim(&mut grid, &counts, &mut work_buffer);
-        if seen.contains(&rating) {
-            return rating;
-        } else {
-            seen.insert(rating);
-        }
-    }
+fn step(grid: &Layout) -> Layout {
+    let cells = grid
+        .positions()
+        .map(|p| next_state(grid[p], grid.neighbors4(p).filter(|&n| grid[n]).count()))
+        .collect();
+    Grid::new(cells, SIZE, SIZE)
 }
 
-pub fn part2(input: &str) -> usize {
-    let mut initial = BitVec::<_, _>::with_capacity(25);
-    for c in input.chars().filter(|c| *c != '\r' && *c != '\n') {
-        initial.push(c == '#');
+pub fn part1(input: &str) -> u32 {
+    let mut grid = parse_layout(input);
+    let mut seen = HashSet::new();
+    while seen.insert(grid.clone()) {
+        grid = step(&grid);
     }
-    let mut grid = RecursiveBugGrid::new(initial);
-    for _ in 0..200 {
+    biodiversity(&grid)
+}
+
+/// Number of bugs after `minutes` in recursive layouts starting from
+/// `input`.
+fn count_bugs_after(input: &str, minutes: usize) -> usize {
+    let mut grid = RecursiveBugGrid::new(parse_layout(input));
+    for _ in 0..minutes {
         grid.sim_step_recursive();
     }
     grid.count_bugs()
 }
 
-const UPPER: &[usize] = &[0, 1, 2, 3, 4];
-const LOWER: &[usize] = &[20, 21, 22, 23, 24];
-const LEFT: &[usize] = &[0, 5, 10, 15, 20];
-const RIGHT: &[usize] = &[4, 9, 14, 19, 24];
-const INNER_CROSS: &[usize] = &[7, 11, 13, 17];
-
-fn count_bugs(positions: &[usize], grid: &BitSlice<LittleEndian, u32>) -> usize {
-    let mut count = 0;
-    for &i in positions {
-        if grid[i] {
-            count += 1
-        }
-    }
-    count
+pub fn part2(input: &str) -> usize {
+    count_bugs_after(input, 200)
 }
 
+/// Layouts nested inside each other through their center tiles, with
+/// lower depths being further out.
 #[derive(Debug)]
 struct RecursiveBugGrid {
-    depths: HashMap<i32, BitVec<LittleEndian, u32>>,
-    work_buffer: BitVec<LittleEndian, u32>,
-    outermost: i32,
-    innermost: i32,
+    depths: BTreeMap<i32, Layout>,
 }
 
 impl RecursiveBugGrid {
-    fn new(start: BitVec<LittleEndian, u32>) -> Self {
-        let level_zero = start.into();
-        let mut depths = HashMap::with_capacity(1);
-        depths.insert(0, level_zero);
-        Self {
-            depths,
-            work_buffer: bitvec![LittleEndian, u32; 0; 25],
-            outermost: 0,
-            innermost: 0,
-        }
+    fn new(start: Layout) -> Self {
+        let mut depths = BTreeMap::new();
+        depths.insert(0, start);
+        Self { depths }
     }
 
-    fn get_at(&self, depth: i32) -> &BitSlice<LittleEndian, u32> {
-        &self.depths[&depth]
+    fn has_bug(&self, depth: i32, pos: Point) -> bool {
+        self.depths.get(&depth).is_some_and(|grid| grid[pos])
     }
 
-    fn outer_spread(&self) -> bool {
-        let outer = self.get_at(self.outermost);
-        for edge in [UPPER, LEFT, RIGHT, LOWER].into_iter() {
-            let count = count_bugs(edge, outer);
-            if count == 1 || count == 2 {
-                return true;
-            }
-        }
-        false
-    }
-
-    fn inner_spread(&self) -> bool {
-        let inner = self.get_at(self.innermost);
-        // if there is any tile with a bug in the innermost inner cross,
-        // then that means at least one whole edge at the lower level is
-        // neighboring a single bug, meaning it will spread
-        INNER_CROSS.iter().map(|i| inner[*i]).any(|i| i)
-    }
-
-    fn calc_neighbor_counts_recursive(&self, depth: i32) -> Vec<usize> {
-        let grid = self.get_at(depth);
-        let mut counts = vec![0; 25];
-        for i in (0..=11).chain(13..25) {
-            let (x, y) = (i % 5, i / 5);
-            let mut count = 0;
-            // additional checks if not the outermost or innermost
-            if self.outermost != depth {
-                let outer = self.get_at(depth - 1);
-                if y == 0 && outer[7] {
-                    count += 1;
-                }
-                if y == 4 && outer[17] {
-                    count += 1;
-                }
-                if x == 0 && outer[11] {
-                    count += 1;
-                }
-                if x == 4 && outer[13] {
-                    count += 1;
-                }
-            }
-            if self.innermost != depth {
-                let inner = self.get_at(depth + 1);
-                if i == 7 {
-                    count += count_bugs(UPPER, &inner);
-                }
-                if i == 11 {
-                    count += count_bugs(LEFT, &inner);
-                }
-                if i == 13 {
-                    count += count_bugs(RIGHT, &inner);
-                }
-                if i == 17 {
-                    count += count_bugs(LOWER, &inner);
-                }
-            }
-            if y > 0 && grid[i - 5] {
-                count += 1;
-            }
-            if y < 4 && grid[i + 5] {
-                count += 1;
-            }
-            if x > 0 && grid[i - 1] {
-                count += 1;
-            }
-            if x < 4 && grid[i + 1] {
-                count += 1;
-            }
-            counts[i] = count;
-        }
-        counts
-    }
-
-    fn apply_sim(&mut self, depth: i32, neighbor_counts: &[usize]) -> u32 {
-        let grid = self.depths.get_mut(&depth).unwrap();
-        for i in 0..25 {
-            let bug = grid[i];
-            let new = if bug && neighbor_counts[i] != 1 {
-                false
-            } else if !bug && (neighbor_counts[i] == 1 || neighbor_counts[i] == 2) {
-                true
+    fn neighbor_count(&self, depth: i32, pos: Point) -> usize {
+        let mut count = 0;
+        for dir in Direction::all() {
+            let next = pos + dir;
+            if next == CENTER {
+                // the whole edge of the layout inside that faces us,
+                // which is the one on the side we came from
+                let last = SIZE as i32 - 1;
+                let edge = (0..SIZE as i32).map(|i| match dir {
+                    Direction::Down => Point::new(i, 0),
+                    Direction::Up => Point::new(i, last),
+                    Direction::Right => Point::new(0, i),
+                    Direction::Left => Point::new(last, i),
+                });
+                count += edge.filter(|&p| self.has_bug(depth + 1, p)).count();
+            } else if self.depths[&depth].contains(next) {
+                count += self.has_bug(depth, next) as usize;
             } else {
-                bug
-            };
-            self.work_buffer.set(i, new);
+                // the tile next to the center of the layout outside
+                count += self.has_bug(depth - 1, CENTER + dir) as usize;
+            }
         }
-        grid.clear();
-        grid.extend(self.work_buffer.drain(..));
-        // this is safe as we know the memory is allocated and we also
-        // won't be reading from it until this same range is
-        // overwritten
-        unsafe { self.work_buffer.set_len(25) };
-        grid.load().unwrap()
+        count
     }
 
     fn sim_step_recursive(&mut self) {
-        if self.outer_spread() {
-            self.outermost -= 1;
-            self.depths
-                .insert(self.outermost, bitvec![LittleEndian, u32; 0; 25]);
-        }
-        if self.inner_spread() {
-            self.innermost += 1;
-            self.depths
-                .insert(self.innermost, bitvec![LittleEndian, u32; 0; 25]);
+        // bugs can only spread one level further in or out per minute
+        let empty = Grid::filled(SIZE, SIZE, false);
+        let (&outermost, outer) = self.depths.iter().next().unwrap();
+        if outer.cells().contains(&true) {
+            self.depths.insert(outermost - 1, empty.clone());
         }
-        let depths: Vec<_> = self.depths.keys().cloned().collect();
-        //println!("{}", depths.len());
-        let mut counts_per_depth = HashMap::with_capacity(depths.len());
-        for depth in depths.iter() {
-            counts_per_depth.insert(depth, self.calc_neighbor_counts_recursive(*depth));
-        }
-        for depth in depths.iter() {
-            self.apply_sim(*depth, &counts_per_depth[depth]);
+        let (&innermost, inner) = self.depths.iter().next_back().unwrap();
+        if inner.cells().contains(&true) {
+            self.depths.insert(innermost + 1, empty);
         }
+        let depths = self
+            .depths
+            .iter()
+            .map(|(&depth, grid)| {
+                let cells = grid
+                    .positions()
+                    .map(|p| p != CENTER && next_state(grid[p], self.neighbor_count(depth, p)))
+                    .collect();
+                (depth, Grid::new(cells, SIZE, SIZE))
+            })
+            .collect();
+        self.depths = depths;
     }
 
     fn count_bugs(&self) -> usize {
         self.depths
             .values()
-            .map(|depth| depth.count_ones())
-            .sum::<usize>()
+            .map(|grid| grid.cells().iter().filter(|&&bug| bug).count())
+            .sum()
+    }
+}
+
+impl_solution!(Day24, 24);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#
+#..#.
+#..##
+..#..
+#....";
+
+    #[test]
+    fn test_spreading() {
+        let after_one = parse_layout(
+            "\
+#..#.
+####.
+###.#
+##.##
+.##..",
+        );
+        assert_eq!(after_one, step(&parse_layout(EXAMPLE)));
+        assert_eq!(2129920, part1(EXAMPLE));
+    }
+
+    #[test]
+    fn test_recursive() {
+        assert_eq!(99, count_bugs_after(EXAMPLE, 10));
     }
 }
@@ -1,34 +1,490 @@
-use crate::intcode::{AsciiTranslator, IntcodeMachine};
-use std::io::BufRead;
-
-// struct Droid {
-//     program: IntcodeMachine,
-// }
-
-// impl Droid {
-//     fn new(source_code: &str) -> Self {
-//         Self {
-//             program: IntcodeMachine::from_str(source_code),
-//         }
-//     }
-// }
-
-pub fn part1(input: &str) {
-    //let mut droid = Droid::new(input);
-    let mut droid = IntcodeMachine::from_str(input);
-    let stdin = std::io::stdin();
-    let stdin = stdin.lock();
-    let mut input = AsciiTranslator::new();
-    let mut output = AsciiTranslator::new();
-    droid.run_while_input(&mut input, &mut output);
-    println!("{}", &output.drain_string());
-    for line in stdin.lines().map(|l| l.unwrap()) {
-        if line.contains("!quit!") {
-            break;
-        }
-        input.push_string(line);
-        output.clear();
-        droid.run_while_input(&mut input, &mut output);
-        println!("{}", &output.drain_string());
+//! Day 25: Cryostasis
+//!
+//! # Problem Description
+//!
+//! A text adventure running on Intcode. The droid has to explore the
+//! ship, pick up the right set of items and walk onto a
+//! pressure-sensitive floor, which only lets it through if its weight
+//! matches. Doing so reveals the password for the main airlock.
+//!
+//! # Implementation Details
+//!
+//! Everything is done by reading the droid's output, so it doesn't
+//! depend on a particular ship layout. The ship is mapped with a BFS
+//! where each frontier room keeps a clone of the droid standing in
+//! it. Every item is tried out on a throwaway clone first, which is
+//! how the deadly ones are found: the game ending, the program
+//! getting stuck (caught by a step budget) or the droid no longer
+//! being able to move. A single droid then walks around picking up all
+//! the safe items and tries item combinations on the floor, using
+//! the "heavier"/"lighter" alerts to skip combinations that can't
+//! work.
+
+use crate::intcode::{AsciiEvent, AsciiIo, IntcodeMachine, RunResult};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// How many instructions a single command may run for before the
+/// droid is considered to be stuck in an infinite loop.
+const STEP_BUDGET: usize = 1_000_000;
+
+#[derive(Debug, Clone, Default)]
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// The droid is waiting for the next command.
+    Waiting,
+    /// The game is over.
+    Halted,
+    /// The program ran past the step budget.
+    Stuck,
+}
+
+/// What the droid printed in response to a command.
+#[derive(Debug)]
+struct Response {
+    /// Every room shown, in order. There may be more than one when the
+    /// droid is thrown out of a room.
+    rooms: Vec<Room>,
+    lines: Vec<String>,
+    outcome: Outcome,
+}
+
+impl Response {
+    fn parse(lines: Vec<String>, result: RunResult) -> Self {
+        enum List {
+            Doors,
+            Items,
+        }
+        let mut rooms: Vec<Room> = Vec::new();
+        let mut list = None;
+        for line in lines.iter().map(|l| l.trim()) {
+            if line.starts_with("== ") && line.ends_with(" ==") {
+                rooms.push(Room {
+                    name: line.trim_matches(|c| c == '=' || c == ' ').to_string(),
+                    ..Room::default()
+                });
+                list = None;
+                continue;
+            }
+            let room = match rooms.last_mut() {
+                Some(room) => room,
+                None => continue,
+            };
+            match line {
+                "" => list = None,
+                "Doors here lead:" => list = Some(List::Doors),
+                "Items here:" => list = Some(List::Items),
+                _ if line.starts_with("- ") => {
+                    let entry = line[2..].to_string();
+                    match list {
+                        Some(List::Doors) => room.doors.push(entry),
+                        Some(List::Items) => room.items.push(entry),
+                        None => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+        let outcome = match result {
+            RunResult::InputRequest => Outcome::Waiting,
+            RunResult::Stop => Outcome::Halted,
+            _ => Outcome::Stuck,
+        };
+        Response {
+            rooms,
+            lines,
+            outcome,
+        }
+    }
+
+    /// The room the droid ended up in, if it moved.
+    fn room(&self) -> Option<&Room> {
+        self.rooms.last()
+    }
+
+    fn contains(&self, text: &str) -> bool {
+        self.lines.iter().any(|l| l.contains(text))
+    }
+}
+
+#[derive(Clone)]
+struct Droid {
+    program: IntcodeMachine,
+}
+
+impl Droid {
+    /// Boots the droid, returning it along with the room it starts in.
+    fn start(source_code: &str) -> (Self, Room) {
+        let mut droid = Droid {
+            program: IntcodeMachine::from_str(source_code),
+        };
+        let response = droid.send("");
+        let room = response
+            .room()
+            .cloned()
+            .expect("Droid didn't describe its starting room");
+        (droid, room)
+    }
+
+    fn send(&mut self, command: &str) -> Response {
+        let mut io = AsciiIo::new();
+        if !command.is_empty() {
+            io.push_str(command);
+            io.push_str("\n");
+        }
+        let result = io.run_with_limit(&mut self.program, STEP_BUDGET);
+        io.flush();
+        let lines = io.drain_events().filter_map(AsciiEvent::line).collect();
+        Response::parse(lines, result)
+    }
+}
+
+/// Everything learned about the ship from exploring it.
+struct ShipMap {
+    start: String,
+    seen: HashSet<String>,
+    /// Where each door of each room leads.
+    doors: HashMap<String, Vec<(String, String)>>,
+    /// Safe items and the room they were found in, in the order they
+    /// were found.
+    items: Vec<(String, String)>,
+    /// The room next to the pressure-sensitive floor and the door
+    /// leading to it.
+    checkpoint: Option<(String, String)>,
+}
+
+impl ShipMap {
+    fn explore(start_droid: &Droid, start: Room) -> Self {
+        let mut map = ShipMap {
+            start: start.name.clone(),
+            seen: HashSet::new(),
+            doors: HashMap::new(),
+            items: Vec::new(),
+            checkpoint: None,
+        };
+        let mut queue = VecDeque::new();
+        queue.push_back((start.clone(), start_droid.clone()));
+        map.seen.insert(start.name.clone());
+        while let Some((room, droid)) = queue.pop_front() {
+            for item in room.items.iter() {
+                if is_safe(&droid, item, &room) {
+                    map.items.push((item.clone(), room.name.clone()));
+                }
+            }
+            for door in room.doors.iter() {
+                let mut next = droid.clone();
+                let response = next.send(door);
+                if response.rooms.len() > 1 {
+                    // entered a room but got thrown back out, which only
+                    // happens on the pressure-sensitive floor
+                    map.checkpoint = Some((room.name.clone(), door.clone()));
+                    continue;
+                }
+                let next_room = match response.room() {
+                    Some(r) => r.clone(),
+                    None => continue,
+                };
+                map.doors
+                    .entry(room.name.clone())
+                    .or_default()
+                    .push((door.clone(), next_room.name.clone()));
+                if map.seen.insert(next_room.name.clone()) {
+                    queue.push_back((next_room, next));
+                }
+            }
+        }
+        map
+    }
+
+    /// Finds the doors to go through to get from one room to another,
+    /// or `None` if no known doors lead there.
+    fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut came_from: HashMap<&str, (&str, &str)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(room) = queue.pop_front() {
+            if room == to {
+                break;
+            }
+            for (door, next) in self.doors.get(room).into_iter().flatten() {
+                if next != from && !came_from.contains_key(next.as_str()) {
+                    came_from.insert(next, (room, door));
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut path = Vec::new();
+        let mut curr = to;
+        while curr != from {
+            let (prev, door) = *came_from.get(curr)?;
+            path.push(door.to_string());
+            curr = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Tries taking an item on a copy of the droid. An item is deadly if
+/// the game ends, the program never asks for input again, or the
+/// droid can't leave the room afterwards.
+fn is_safe(droid: &Droid, item: &str, room: &Room) -> bool {
+    let mut droid = droid.clone();
+    let response = droid.send(&format!("take {}", item));
+    if response.outcome != Outcome::Waiting {
+        return false;
+    }
+    match room.doors.first() {
+        Some(door) => {
+            let response = droid.send(door);
+            response.outcome == Outcome::Waiting && response.room().is_some()
+        }
+        None => true,
+    }
+}
+
+fn find_password(response: &Response) -> Option<String> {
+    response
+        .lines
+        .iter()
+        .filter(|l| l.contains("typing"))
+        .flat_map(|l| l.split_whitespace())
+        .find(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()))
+        .map(String::from)
+}
+
+/// What the pressure-sensitive floor made of the droid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Weighing {
+    /// Other droids are heavier.
+    TooLight,
+    /// Other droids are lighter.
+    TooHeavy,
+    /// Let through, with the password.
+    Passed(String),
+    /// Anything else, which rules out nothing.
+    Unknown,
+}
+
+/// Standing at the checkpoint holding all `items`, drops the ones not
+/// in the `held` bit set and steps on the floor.
+fn weigh(droid: &Droid, items: &[String], held: u32, floor_door: &str) -> Weighing {
+    let mut droid = droid.clone();
+    for (i, item) in items.iter().enumerate() {
+        if held & (1 << i) == 0 {
+            droid.send(&format!("drop {}", item));
+        }
+    }
+    let response = droid.send(floor_door);
+    if response.contains("heavier than the detected") {
+        Weighing::TooLight
+    } else if response.contains("lighter than the detected") {
+        Weighing::TooHeavy
+    } else if let Some(password) = find_password(&response) {
+        Weighing::Passed(password)
+    } else {
+        Weighing::Unknown
+    }
+}
+
+/// Weighs combinations of `count` items, given as bit sets, until one
+/// is let through. If a combination is too light, so are all of its
+/// subsets, and if it's too heavy so are all of its supersets, so
+/// those get skipped.
+fn search_combinations(count: usize, mut weigh: impl FnMut(u32) -> Weighing) -> Option<String> {
+    let mut too_light: Vec<u32> = Vec::new();
+    let mut too_heavy: Vec<u32> = Vec::new();
+    for held in (0..1u32 << count).rev() {
+        if too_light.iter().any(|&l| held & !l == 0) || too_heavy.iter().any(|&h| h & !held == 0) {
+            continue;
+        }
+        match weigh(held) {
+            Weighing::TooLight => too_light.push(held),
+            Weighing::TooHeavy => too_heavy.push(held),
+            Weighing::Passed(password) => return Some(password),
+            Weighing::Unknown => (),
+        }
+    }
+    None
+}
+
+pub fn part1(input: &str) -> String {
+    let (mut droid, start) = Droid::start(input);
+    let map = ShipMap::explore(&droid, start);
+    let (checkpoint, floor_door) = map
+        .checkpoint
+        .clone()
+        .expect("Couldn't find the pressure-sensitive floor");
+
+    // items in rooms the droid can't walk to are left behind
+    let mut curr = map.start.clone();
+    let mut items = Vec::new();
+    for (item, room) in map.items.iter() {
+        let path = match map.path(&curr, room) {
+            Some(path) => path,
+            None => continue,
+        };
+        for door in path {
+            droid.send(&door);
+        }
+        droid.send(&format!("take {}", item));
+        items.push(item.clone());
+        curr = room.clone();
+    }
+    let path = map
+        .path(&curr, &checkpoint)
+        .expect("Couldn't find the way to the checkpoint");
+    for door in path {
+        droid.send(&door);
+    }
+
+    search_combinations(items.len(), |held| weigh(&droid, &items, held, &floor_door))
+        .expect("No combination of items got through the floor")
+}
+
+impl_solution!(Day25, 25, no_part2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("../../input/25-1.txt");
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let text = "
+
+== Hull Breach ==
+You got in through a hole in the floor here.
+
+Doors here lead:
+- north
+- west
+
+Items here:
+- mug
+- infinite loop
+
+Command?";
+        let response = Response::parse(lines(text), RunResult::InputRequest);
+        assert_eq!(Outcome::Waiting, response.outcome);
+        let room = response.room().unwrap();
+        assert_eq!("Hull Breach", room.name);
+        assert_eq!(vec!["north", "west"], room.doors);
+        assert_eq!(vec!["mug", "infinite loop"], room.items);
+
+        let text = "
+
+== Pressure-Sensitive Floor ==
+Analyzing...
+
+Doors here lead:
+- south
+
+A loud, robotic voice says \"Alert! Droids on this ship are heavier than the detected value!\" and you are ejected back to the checkpoint.
+
+
+
+== Security Checkpoint ==
+In the next room, a pressure-sensitive floor will verify your identity.
+
+Doors here lead:
+- north
+- east
+
+Command?";
+        let response = Response::parse(lines(text), RunResult::InputRequest);
+        assert_eq!(2, response.rooms.len());
+        assert_eq!("Security Checkpoint", response.room().unwrap().name);
+        assert!(response.room().unwrap().items.is_empty());
+        assert!(response.contains("heavier than the detected"));
+        assert_eq!(None, find_password(&response));
+
+        let text = "\"Oh, hello! You should be able to get in by typing 2424308736 on the keypad at the main airlock.\"";
+        let response = Response::parse(lines(text), RunResult::Stop);
+        assert_eq!(Outcome::Halted, response.outcome);
+        assert!(response.room().is_none());
+        assert_eq!(Some("2424308736".to_string()), find_password(&response));
+    }
+
+    #[test]
+    fn test_path() {
+        let door = |door: &str, to: &str| (door.to_string(), to.to_string());
+        let mut doors = HashMap::new();
+        doors.insert("a".to_string(), vec![door("north", "b"), door("east", "c")]);
+        doors.insert("b".to_string(), vec![door("south", "a")]);
+        doors.insert("c".to_string(), vec![door("west", "a"), door("east", "d")]);
+        let map = ShipMap {
+            start: "a".to_string(),
+            seen: HashSet::new(),
+            doors,
+            items: Vec::new(),
+            checkpoint: None,
+        };
+        assert_eq!(
+            Some(vec![
+                "south".to_string(),
+                "east".to_string(),
+                "east".to_string()
+            ]),
+            map.path("b", "d")
+        );
+        assert_eq!(Some(Vec::new()), map.path("c", "c"));
+        // d's doors were never seen
+        assert_eq!(None, map.path("d", "a"));
+        assert_eq!(None, map.path("a", "e"));
+    }
+
+    #[test]
+    fn test_search_combinations() {
+        let weights = [5, 9, 14, 22, 40, 71, 95, 130];
+        let floor = |target: u32, tried: &mut Vec<u32>| {
+            search_combinations(weights.len(), |held| {
+                tried.push(held);
+                let weight: u32 = (0..weights.len())
+                    .filter(|&i| held & (1 << i) != 0)
+                    .map(|i| weights[i])
+                    .sum();
+                if weight < target {
+                    Weighing::TooLight
+                } else if weight > target {
+                    Weighing::TooHeavy
+                } else {
+                    Weighing::Passed(held.to_string())
+                }
+            })
+        };
+
+        let mut tried = Vec::new();
+        let held = 0b1010_0110;
+        assert_eq!(Some(held.to_string()), floor(9 + 14 + 71 + 130, &mut tried));
+        // nothing is ever tried twice, and far fewer than all of them
+        // are tried at all
+        let mut unique = tried.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(tried.len(), unique.len());
+        assert!(
+            tried.len() * 2 < 1 << weights.len(),
+            "{} tried",
+            tried.len()
+        );
+
+        // no combination is exactly this heavy
+        assert_eq!(None, floor(1, &mut Vec::new()));
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!("2424308736", part1(INPUT));
     }
 }
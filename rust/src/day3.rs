@@ -23,21 +23,7 @@
 use itertools::Itertools;
 use std::collections::HashSet;
 
-use crate::util::Point;
-
-fn dir_tuple(s: char) -> (i32, i32) {
-    match s {
-        'U' => (0, -1),
-        'D' => (0, 1),
-        'L' => (-1, 0),
-        'R' => (1, 0),
-        _ => panic!("Invalid direction"),
-    }
-}
-
-fn manhattan_dist(a: &Point, b: &Point) -> usize {
-    ((a.x - b.x).abs() + (a.y - b.y).abs()) as usize
-}
+use crate::util::{Direction, Point};
 
 pub struct Wire {
     points: Vec<Point>,
@@ -55,15 +41,13 @@ impl Wire {
 pub fn points_from_segments<'a>(segments: impl IntoIterator<Item = &'a str>) -> Wire {
     let segments = segments.into_iter();
     let mut dir_mods = segments.flat_map(|segment| {
-        let (x, y) = dir_tuple(segment.chars().nth(0).unwrap());
+        let dir = segment[..1].parse::<Direction>().unwrap();
         let steps = segment[1..].parse().unwrap();
-        std::iter::repeat((x, y)).take(steps)
+        std::iter::repeat_n(Point::from(dir), steps)
     });
-    let points = itertools::unfold(Point::new(0, 0), move |curr_pos| {
-        let (x, y) = dir_mods.next()?;
-        curr_pos.x = curr_pos.x + x;
-        curr_pos.y = curr_pos.y + y;
-        Some(curr_pos.clone())
+    let points = itertools::unfold(Point::origin(), move |curr_pos| {
+        *curr_pos += dir_mods.next()?;
+        Some(*curr_pos)
     })
     .collect();
     Wire { points }
@@ -84,8 +68,7 @@ fn find_closest_intersection(a: &Wire, b: &Wire, dist: impl Fn(&Point) -> usize)
 
 pub fn part1(input: &str) -> usize {
     let (wire_a, wire_b) = parse_wires(input);
-    let origin = Point::new(0, 0);
-    let dist = |p: &Point| manhattan_dist(&origin, p);
+    let dist = |p: &Point| p.manhattan(Point::origin()) as usize;
     find_closest_intersection(&wire_a, &wire_b, dist)
 }
 
@@ -95,6 +78,8 @@ pub fn part2(input: &str) -> usize {
     find_closest_intersection(&wire_a, &wire_b, dist)
 }
 
+impl_solution!(Day3, 3);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,11 +96,11 @@ mod tests {
             .into_iter()
             .map(|p| Point::new(p.0, p.1))
             .collect();
-        assert_eq!(5, manhattan_dist(&origin, &points[0]));
-        assert_eq!(5, manhattan_dist(&origin, &points[1]));
-        assert_eq!(5, manhattan_dist(&origin, &points[2]));
-        assert_eq!(5, manhattan_dist(&origin, &points[3]));
-        assert_eq!(10, manhattan_dist(&origin, &points[4]));
+        assert_eq!(5, origin.manhattan(points[0]));
+        assert_eq!(5, origin.manhattan(points[1]));
+        assert_eq!(5, origin.manhattan(points[2]));
+        assert_eq!(5, origin.manhattan(points[3]));
+        assert_eq!(10, origin.manhattan(points[4]));
         let points: Vec<_> = [
             (0, 1),
             (0, 2),
@@ -66,3 +66,13 @@ pub fn part1_part2(input: &str) -> (u32, u32) {
     }
     (p1_count, p2_count)
 }
+
+pub fn part1(input: &str) -> u32 {
+    part1_part2(input).0
+}
+
+pub fn part2(input: &str) -> u32 {
+    part1_part2(input).1
+}
+
+impl_solution!(Day4, 4);
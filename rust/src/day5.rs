@@ -15,14 +15,23 @@
 
 use crate::intcode;
 
+/// Runs the diagnostic program for the given system ID. Every output
+/// but the last is a test result that should be 0, and the last one is
+/// the diagnostic code.
+fn run_diagnostics(input: &str, system_id: i64) -> i64 {
+    let mut outputs = Vec::new();
+    intcode::run_from_str(input, &mut Some(system_id), &mut outputs);
+    let (code, tests) = outputs.split_last().expect("No diagnostic code output");
+    assert!(tests.iter().all(|&t| t == 0), "Failed tests: {:?}", tests);
+    *code
+}
+
 pub fn part1(input: &str) -> i64 {
-    //let stdin = std::io::stdin();
-    //let input = stdin.lock().lines();
-    intcode::run_from_str(input, &mut Some(1), &mut std::io::sink())
+    run_diagnostics(input, 1)
 }
 
 pub fn part2(input: &str) -> i64 {
-    //let stdin = std::io::stdin();
-    //let input = stdin.lock().lines();
-    intcode::run_from_str(input, &mut Some(5), &mut std::io::sink())
+    run_diagnostics(input, 5)
 }
+
+impl_solution!(Day5, 5);
@@ -48,6 +48,8 @@ use petgraph::{algo::toposort, prelude::*};
 
 use std::collections::HashMap;
 
+use crate::util::search;
+
 pub fn parse_graph(input: &str) -> (DiGraph<(), ()>, HashMap<&str, NodeIndex>) {
     let mut nodes = HashMap::new();
     let mut graph: DiGraph<_, _> = DiGraph::new();
@@ -104,47 +106,25 @@ pub fn part1_graphmap(input: &str) -> u32 {
 
 pub fn part2(input: &str) -> u32 {
     let (graph, nodes) = parse_graph(input);
-    let graph = graph.into_edge_type::<petgraph::Undirected>();
     let (you, san) = (nodes["YOU"], nodes["SAN"]);
-    let mut distances = HashMap::<NodeIndex, u32>::new();
-    distances.insert(you, 0);
-    let mut bfs = Bfs::new(&graph, you);
-    while let Some(node) = bfs.next(&graph) {
-        if node == san {
-            return distances[&san] - 2;
-        }
-        let mut neighbors = graph.neighbors_undirected(node).detach();
-        while let Some(neighbor) = neighbors.next_node(&graph) {
-            if distances.contains_key(&neighbor) {
-                continue;
-            } else {
-                distances.insert(neighbor, distances[&node] + 1);
-            }
-        }
-    }
-    0
+    transfers(
+        search::bfs(Some(you), |&n| graph.neighbors_undirected(n)),
+        san,
+    )
+}
+
+/// Orbital transfers needed to get to the object `to` orbits, given a
+/// search starting from `YOU`. Neither `YOU` nor `to` is a transfer
+/// themselves, hence the two steps removed.
+fn transfers<S: PartialEq>(mut search: impl Iterator<Item = (S, usize)>, to: S) -> u32 {
+    search
+        .find(|(node, _)| *node == to)
+        .map_or(0, |(_, dist)| dist as u32 - 2)
 }
 
 pub fn part2_graphmap(input: &str) -> u32 {
-    let graph = parse_graphmap::<petgraph::Directed>(input);
-    let (start, end) = ("YOU", "SAN");
-    let mut distances = HashMap::<&str, u32>::new();
-    distances.insert(start, 0);
-    let mut bfs = Bfs::new(&graph, start);
-    while let Some(node) = bfs.next(&graph) {
-        if node == end {
-            return distances[end] - 2;
-        }
-        let neighbors = graph.neighbors(node);
-        for neighbor in neighbors {
-            if distances.contains_key(&neighbor) {
-                continue;
-            } else {
-                distances.insert(neighbor, distances[&node] + 1);
-            }
-        }
-    }
-    0
+    let graph = parse_graphmap::<petgraph::Undirected>(input);
+    transfers(search::bfs(Some("YOU"), |&n| graph.neighbors(n)), "SAN")
 }
 
 #[allow(dead_code)]
@@ -156,3 +136,5 @@ fn find_key<'a>(index: &NodeIndex, nodes: &HashMap<&'a str, NodeIndex>) -> &'a s
     }
     unreachable!("No node str corresponding to index - should be impossible");
 }
+
+impl_solution!(Day6, 6);
@@ -67,3 +67,5 @@ pub fn part1(input: &str) -> i64 {
 pub fn part2(input: &str) -> i64 {
     find_max_thruster(input, 5..10, std::usize::MAX)
 }
+
+impl_solution!(Day7, 7);
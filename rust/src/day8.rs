@@ -1,72 +1,42 @@
-use itertools::Itertools;
+pub mod sif;
+
+use crate::util::ocr;
+use sif::{Pixel, SpaceImage};
 
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
 
+fn parse(input: &str) -> SpaceImage {
+    SpaceImage::parse(input, WIDTH, HEIGHT).unwrap_or_else(|e| panic!("Invalid image: {}", e))
+}
+
 pub fn part1(input: &str) -> usize {
-    let digits = input
-        .trim()
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as usize);
-    let layer_counts = digits
-        .chunks(WIDTH * HEIGHT)
-        .into_iter()
-        .map(|layer| {
-            // there are only 3 possible digits
-            let mut counts = vec![0; 3];
-            for d in layer {
-                counts[d] += 1;
-            }
-            counts
-        })
+    let image = parse(input);
+    let counts = (0..image.num_layers())
+        .map(|i| image.digit_counts(i))
         .min_by_key(|c| c[0])
         .unwrap();
-    layer_counts[1] * layer_counts[2]
-}
-
-#[derive(Debug, Clone, Copy)]
-enum PixelColor {
-    Black,
-    White,
-    Transparent,
+    counts[1] * counts[2]
 }
 
-impl From<char> for PixelColor {
-    fn from(c: char) -> Self {
-        match c {
-            '0' => PixelColor::Black,
-            '1' => PixelColor::White,
-            '2' => PixelColor::Transparent,
-            _ => panic!("Invalid pixel digit!"),
-        }
-    }
+pub fn part2(input: &str) -> String {
+    let image = parse(input);
+    let lit = image.composite().map(|&p| p == Pixel::White);
+    ocr::recognize(&lit)
+        .unwrap_or_else(|e| panic!("Can't read the message: {}\n{}", e, image.render()))
 }
 
-fn get_color(layers: &[PixelColor], idx: usize, num_layers: usize) -> PixelColor {
-    for l in 0..num_layers {
-        match layers[idx + (l * WIDTH * HEIGHT)] {
-            PixelColor::Transparent => continue,
-            c => return c,
-        }
-    }
-    PixelColor::Transparent
-}
+impl_solution!(Day8, 8);
 
-pub fn part2(input: &str) {
-    let image_len = input.len();
-    let num_layers = (image_len / WIDTH) * HEIGHT;
-    //let mut layers = vec![Vec::with_capacity(image_len); num_layers];
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let layers: Vec<PixelColor> = input.trim().chars().map(PixelColor::from).collect();
+    const INPUT: &str = include_str!("../../input/08-1.txt");
 
-    for i in 0..(WIDTH * HEIGHT) {
-        match get_color(&layers, i, num_layers) {
-            PixelColor::Black => print!("▓"),
-            PixelColor::White => print!("░"),
-            PixelColor::Transparent => print!("  "),
-        }
-        if (i + 1) % WIDTH == 0 {
-            println!();
-        }
+    #[test]
+    fn test_answers() {
+        assert_eq!(1548, part1(INPUT));
+        assert_eq!("CEKUA", part2(INPUT));
     }
 }
@@ -0,0 +1,229 @@
+//! The Space Image Format, which encodes an image as a series of
+//! layers of digits, each `width * height` digits long, one row after
+//! another.
+
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use crate::util::{pnm, Matrix};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pixel {
+    Black,
+    White,
+    Transparent,
+}
+
+impl Pixel {
+    /// Any digit other than 0 (black) or 1 (white) is transparent.
+    fn from_digit(digit: u8) -> Self {
+        match digit {
+            0 => Pixel::Black,
+            1 => Pixel::White,
+            _ => Pixel::Transparent,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Pixel::Black => '.',
+            Pixel::White => '#',
+            Pixel::Transparent => ' ',
+        }
+    }
+
+    fn gray(self) -> u8 {
+        match self {
+            Pixel::Black => 0,
+            Pixel::White => 255,
+            Pixel::Transparent => 128,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SifError {
+    /// Width or height is zero.
+    ZeroSize,
+    /// Something other than a digit at the given position.
+    InvalidDigit { index: usize, found: char },
+    /// The data isn't made of whole layers.
+    BadLength { len: usize, layer_size: usize },
+}
+
+impl fmt::Display for SifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SifError::ZeroSize => write!(f, "image width and height must be positive"),
+            SifError::InvalidDigit { index, found } => {
+                write!(f, "invalid digit {:?} at position {}", found, index)
+            }
+            SifError::BadLength { len, layer_size } => write!(
+                f,
+                "{} digits don't make up whole layers of {}",
+                len, layer_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SifError {}
+
+#[derive(Debug, Clone)]
+pub struct SpaceImage {
+    width: usize,
+    height: usize,
+    digits: Vec<u8>,
+}
+
+impl SpaceImage {
+    /// Parses an image with the given dimensions. Whitespace around
+    /// the digits is ignored.
+    pub fn parse(input: &str, width: usize, height: usize) -> Result<Self, SifError> {
+        if width == 0 || height == 0 {
+            return Err(SifError::ZeroSize);
+        }
+        let digits = input
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(index, c)| match c.to_digit(10) {
+                Some(d) => Ok(d as u8),
+                None => Err(SifError::InvalidDigit { index, found: c }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let layer_size = width * height;
+        if digits.is_empty() || digits.len() % layer_size != 0 {
+            return Err(SifError::BadLength {
+                len: digits.len(),
+                layer_size,
+            });
+        }
+        Ok(SpaceImage {
+            width,
+            height,
+            digits,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn layer_size(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn num_layers(&self) -> usize {
+        self.digits.len() / self.layer_size()
+    }
+
+    /// The digits of layer `i`, the first layer being the top one.
+    pub fn layer(&self, i: usize) -> &[u8] {
+        let size = self.layer_size();
+        &self.digits[i * size..(i + 1) * size]
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = &[u8]> {
+        self.digits.chunks(self.layer_size())
+    }
+
+    /// How many times each digit appears in layer `i`.
+    pub fn digit_counts(&self, i: usize) -> [usize; 10] {
+        let mut counts = [0; 10];
+        for &d in self.layer(i) {
+            counts[d as usize] += 1;
+        }
+        counts
+    }
+
+    /// Stacks the layers, with each pixel taking the color of the top
+    /// layer that isn't transparent there.
+    pub fn composite(&self) -> Matrix<Pixel> {
+        let pixels = (0..self.layer_size())
+            .map(|i| {
+                self.layers()
+                    .map(|layer| Pixel::from_digit(layer[i]))
+                    .find(|&p| p != Pixel::Transparent)
+                    .unwrap_or(Pixel::Transparent)
+            })
+            .collect();
+        Matrix::wrap(pixels, self.width)
+    }
+
+    /// Draws the composite image with `#` for white, `.` for black and
+    /// a blank for transparent pixels.
+    pub fn render(&self) -> String {
+        let mut s = String::new();
+        for row in self.composite().rows() {
+            s.extend(row.iter().map(|p| p.to_char()));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Writes the composite image as a PGM image, with each pixel
+    /// taking up a `scale`x`scale` square. Transparent pixels are gray.
+    pub fn write_pgm<W: Write>(&self, w: &mut W, scale: usize) -> io::Result<()> {
+        let image = self.composite();
+        let (width, height) = (self.width * scale, self.height * scale);
+        let pixels = (0..height)
+            .flat_map(|py| (0..width).map(move |px| (px, py)))
+            .map(|(px, py)| image[(px / scale, py / scale)].gray());
+        pnm::write_pgm(w, width, height, pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        let image = SpaceImage::parse("123456789012", 3, 2).unwrap();
+        assert_eq!(2, image.num_layers());
+        assert_eq!(&[1, 2, 3, 4, 5, 6], image.layer(0));
+        assert_eq!(&[7, 8, 9, 0, 1, 2], image.layer(1));
+        assert_eq!(1, image.digit_counts(1)[0]);
+        assert_eq!(0, image.digit_counts(0)[0]);
+    }
+
+    #[test]
+    fn test_composite() {
+        let image = SpaceImage::parse("0222112222120000", 2, 2).unwrap();
+        let composite = image.composite();
+        assert_eq!(
+            &[Pixel::Black, Pixel::White, Pixel::White, Pixel::Black],
+            composite.inner()
+        );
+        assert_eq!(".#\n#.\n", image.render());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Err(SifError::BadLength {
+                len: 11,
+                layer_size: 6
+            }),
+            SpaceImage::parse("12345678901", 3, 2).map(|_| ())
+        );
+        assert_eq!(
+            Err(SifError::InvalidDigit {
+                index: 2,
+                found: 'x'
+            }),
+            SpaceImage::parse("12x4", 2, 2).map(|_| ())
+        );
+        assert_eq!(
+            Err(SifError::ZeroSize),
+            SpaceImage::parse("1234", 0, 2).map(|_| ())
+        );
+    }
+}
@@ -11,3 +11,5 @@ pub fn part2(input: &str) -> i64 {
     run_from_str(input, &mut Some(2), &mut out);
     out.unwrap()
 }
+
+impl_solution!(Day9, 9);
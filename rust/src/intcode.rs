@@ -3,7 +3,7 @@ mod io;
 mod opcode;
 
 pub use instruction::Instruction;
-pub use io::{IntcodeInput, IntcodeOutput, AsciiTranslator};
+pub use io::{AsciiEvent, AsciiIo, AsciiTranslator, IntcodeInput, IntcodeOutput};
 pub use opcode::Opcode;
 
 /// Convenience function for early days to just run a program with no
@@ -98,6 +98,10 @@ impl<'a> IntcodeMachine {
         self.mem.mem[i]
     }
 
+    pub fn set(&mut self, i: usize, value: i64) {
+        *self.mem.get_mut(i as i64) = value;
+    }
+
     pub fn is_stopped(&self) -> bool {
         self.stopped
     }
@@ -141,6 +145,29 @@ impl<'a> IntcodeMachine {
         RunResult::Stop
     }
 
+    /// Same as `run_while_input`, but gives up after executing `limit`
+    /// instructions, in which case `RunResult::Continue` is
+    /// returned. Useful for programs that might never stop on their
+    /// own.
+    pub fn run_with_limit<I, O>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+        limit: usize,
+    ) -> RunResult
+    where
+        I: IntcodeInput,
+        O: IntcodeOutput,
+    {
+        for _ in 0..limit {
+            match self.step(input, output) {
+                r @ RunResult::InputRequest | r @ RunResult::Stop => return r,
+                _ => (),
+            }
+        }
+        RunResult::Continue
+    }
+
     /// Runs the machine with specified input, stopping after the
     /// first input instruction. This doesn't assume the very next
     /// instruction will be an input instruction, running normally
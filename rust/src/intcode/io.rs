@@ -1,3 +1,4 @@
+use super::{IntcodeMachine, RunResult};
 use std::{collections::VecDeque, io::Write};
 
 pub trait IntcodeInput {
@@ -35,6 +36,12 @@ impl IntcodeInput for Vec<i64> {
     }
 }
 
+impl IntcodeInput for VecDeque<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
 impl IntcodeInput for Option<i64> {
     fn read(&mut self) -> Option<i64> {
         self.take()
@@ -70,6 +77,9 @@ impl IntcodeOutput for Option<i64> {
     }
 }
 
+/// Text queued as input to a program, or gathered from its output.
+/// Every output is cast to a byte, so values outside the ASCII range
+/// come out garbled, see [`AsciiIo`] to get them separately instead.
 pub struct AsciiTranslator {
     string: std::collections::VecDeque<char>,
 }
@@ -106,3 +116,199 @@ impl IntcodeOutput for AsciiTranslator {
         self.string.push_back(out as u8 as char)
     }
 }
+
+/// Something an ASCII program outputs, as seen by [`AsciiIo`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiEvent {
+    /// A full line of text, without the trailing newline.
+    Line(String),
+    /// An output outside of the ASCII range, usually the answer a
+    /// program prints after all its text (e.g. days 17 and 21).
+    Value(i64),
+}
+
+impl AsciiEvent {
+    pub fn line(self) -> Option<String> {
+        match self {
+            AsciiEvent::Line(line) => Some(line),
+            AsciiEvent::Value(_) => None,
+        }
+    }
+
+    pub fn value(self) -> Option<i64> {
+        match self {
+            AsciiEvent::Value(v) => Some(v),
+            AsciiEvent::Line(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LineBuffer {
+    partial: String,
+    events: VecDeque<AsciiEvent>,
+}
+
+impl LineBuffer {
+    fn flush(&mut self) {
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            self.events.push_back(AsciiEvent::Line(line));
+        }
+    }
+}
+
+impl IntcodeOutput for LineBuffer {
+    fn write(&mut self, out: i64) {
+        match out {
+            10 => {
+                let line = std::mem::take(&mut self.partial);
+                self.events.push_back(AsciiEvent::Line(line));
+            }
+            0..=127 => self.partial.push(out as u8 as char),
+            _ => {
+                // keep events in the order they were output
+                self.flush();
+                self.events.push_back(AsciiEvent::Value(out));
+            }
+        }
+    }
+}
+
+/// Input and output for programs that talk in ASCII. Input is queued
+/// as text, while output is gathered into complete lines, with any
+/// non-ASCII value coming through as a separate
+/// [`AsciiEvent::Value`] instead of being cast to a char.
+///
+/// As the same value can't be borrowed as both the input and output
+/// of a machine, use [`AsciiIo::run`] to run a program with it.
+#[derive(Debug, Default)]
+pub struct AsciiIo {
+    input: VecDeque<i64>,
+    output: LineBuffer,
+}
+
+impl AsciiIo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the chars of `s` as input exactly as given. Programs
+    /// only act on input after a newline, so it's up to the caller to
+    /// add one where needed.
+    pub fn push_str(&mut self, s: &str) {
+        self.input.extend(s.chars().map(|c| c as i64));
+    }
+
+    /// Runs the machine until it halts or asks for more input than is
+    /// queued.
+    pub fn run(&mut self, machine: &mut IntcodeMachine) -> RunResult {
+        machine.run_while_input(&mut self.input, &mut self.output)
+    }
+
+    /// Runs the machine for at most `limit` instructions, see
+    /// [`IntcodeMachine::run_with_limit`].
+    pub fn run_with_limit(&mut self, machine: &mut IntcodeMachine, limit: usize) -> RunResult {
+        machine.run_with_limit(&mut self.input, &mut self.output, limit)
+    }
+
+    /// Returns the oldest output event not yet taken.
+    pub fn next_event(&mut self) -> Option<AsciiEvent> {
+        self.output.events.pop_front()
+    }
+
+    /// Takes all complete output events so far, in order.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = AsciiEvent> + '_ {
+        self.output.events.drain(..)
+    }
+
+    /// Text output after the last newline, e.g. a prompt waiting for
+    /// input on the same line.
+    pub fn pending_text(&self) -> &str {
+        &self.output.partial
+    }
+
+    /// Turns any pending text into a line of its own.
+    pub fn flush(&mut self) {
+        self.output.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_str(out: &mut impl IntcodeOutput, s: &str) {
+        for c in s.chars() {
+            out.write(c as i64);
+        }
+    }
+
+    fn lines(lines: &[&str]) -> Vec<AsciiEvent> {
+        lines
+            .iter()
+            .map(|l| AsciiEvent::Line(l.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_line_buffer() {
+        let mut buffer = LineBuffer::default();
+        write_str(&mut buffer, "ab\n\ncd\nef");
+        assert_eq!(lines(&["ab", "", "cd"]), Vec::from(buffer.events.clone()));
+        assert_eq!("ef", buffer.partial);
+
+        // a value ends the pending text, and comes before what's next
+        buffer.events.clear();
+        buffer.write(1234);
+        write_str(&mut buffer, "gh\n");
+        buffer.write(-1);
+        buffer.write(128);
+        let events: Vec<_> = buffer.events.drain(..).collect();
+        assert_eq!(
+            vec![
+                AsciiEvent::Line("ef".to_string()),
+                AsciiEvent::Value(1234),
+                AsciiEvent::Line("gh".to_string()),
+                AsciiEvent::Value(-1),
+                AsciiEvent::Value(128),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_ascii_io() {
+        let mut io = AsciiIo::new();
+        io.push_str("hi\nyo?");
+        assert_eq!(
+            vec![104, 105, 10, 121, 111, 63],
+            Vec::from(io.input.clone())
+        );
+
+        // echoes its input back until it runs out
+        let mut echo = IntcodeMachine::copy_program(&[3, 7, 4, 7, 1105, 1, 0, 0]);
+        io.run(&mut echo);
+        assert!(io.input.is_empty());
+        assert_eq!(Some(AsciiEvent::Line("hi".to_string())), io.next_event());
+        assert_eq!(None, io.next_event());
+        assert_eq!("yo?", io.pending_text());
+
+        io.flush();
+        assert_eq!("", io.pending_text());
+        io.flush();
+        assert_eq!(lines(&["yo?"]), io.drain_events().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ascii_translator() {
+        let mut translator = AsciiTranslator::new();
+        write_str(&mut translator, "ok\n");
+        assert_eq!("ok\n", translator.drain_string());
+
+        translator.push_string("A".to_string());
+        assert_eq!(Some(65), translator.read());
+        assert_eq!(Some(10), translator.read());
+        assert_eq!(None, translator.read());
+    }
+}
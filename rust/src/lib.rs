@@ -1,3 +1,7 @@
+#[macro_use]
+pub mod solution;
+
+pub mod answers;
 pub mod intcode;
 pub mod util;
 
@@ -19,7 +23,7 @@ pub mod day14;
 pub mod day15;
 pub mod day16;
 pub mod day17;
-//pub mod day18;
+pub mod day18;
 pub mod day19;
 pub mod day20;
 pub mod day21;
@@ -1,12 +1,250 @@
-use adventofcode2019::*;
+use adventofcode2019::{
+    answers::{Answers, Check},
+    solution::{self, Answer, Solution},
+};
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
+    time::Instant,
+};
+
+const USAGE: &str = "\
+Usage: adventofcode2019 <day|all> [--part <1|2>] [--input <path|->]
+                        [--check | --record [--force]] [--answers <path>]
+
+Runs the solutions for the given day, or for every day with `all`.
+Input is read from ../input/DD-1.txt by default, or from the given
+path, or from stdin when the path is `-`.
+
+--check compares every answer with the ones in the answers file
+(../input/answers.txt by default), failing if any is wrong or missing.
+--record saves the answers that aren't in the file yet, and with
+--force it also replaces the ones that differ.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Run,
+    Check,
+    Record,
+}
+
+struct Args {
+    day: Option<u32>,
+    part: Option<u32>,
+    input: Option<String>,
+    mode: Mode,
+    force: bool,
+    answers: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let day = match args.next().as_deref() {
+        Some("all") => None,
+        Some(d) => Some(d.parse().map_err(|_| format!("Invalid day `{}`", d))?),
+        None => return Err("Missing day".to_string()),
+    };
+    let mut parsed = Args {
+        day,
+        part: None,
+        input: None,
+        mode: Mode::Run,
+        force: false,
+        answers: format!("{}/../input/answers.txt", env!("CARGO_MANIFEST_DIR")),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for `{}`", arg))
+        };
+        match arg.as_str() {
+            "--part" => {
+                let value = value()?;
+                match value.parse() {
+                    Ok(p @ 1..=2) => parsed.part = Some(p),
+                    _ => return Err(format!("Invalid part `{}`", value)),
+                }
+            }
+            "--input" => parsed.input = Some(value()?),
+            "--answers" => parsed.answers = value()?,
+            "--check" | "--record" if parsed.mode != Mode::Run => {
+                return Err("Only one of --check and --record can be used".to_string())
+            }
+            "--check" => parsed.mode = Mode::Check,
+            "--record" => parsed.mode = Mode::Record,
+            "--force" => parsed.force = true,
+            _ => return Err(format!("Unknown argument `{}`", arg)),
+        }
+    }
+    if parsed.force && parsed.mode != Mode::Record {
+        return Err("--force can only be used with --record".to_string());
+    }
+    if parsed.day.is_none() && parsed.input.is_some() {
+        return Err("--input can't be used with `all`".to_string());
+    }
+    Ok(parsed)
+}
+
+fn read_input(day: u32, path: Option<&str>) -> io::Result<String> {
+    match path {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path),
+        None => fs::read_to_string(format!(
+            "{}/../input/{:02}-1.txt",
+            env!("CARGO_MANIFEST_DIR"),
+            day
+        )),
+    }
+}
+
+fn run(solution: &dyn Solution, parts: &[u32], input: &str) -> Vec<(u32, Answer)> {
+    let mut answers = Vec::new();
+    for &part in parts {
+        let start = Instant::now();
+        let answer = solution.part(part, input);
+        let elapsed = start.elapsed();
+        println!(
+            "Day {:2} part {}: {} ({:.2?})",
+            solution.day(),
+            part,
+            answer,
+            elapsed
+        );
+        answers.push((part, answer));
+    }
+    answers
+}
+
+/// Tally of how the answers of a run compare to the known ones.
+#[derive(Default)]
+struct Report {
+    matched: usize,
+    mismatched: Vec<String>,
+    /// Parts with no answer to compare, either because none is on
+    /// record or because the day's input couldn't be read.
+    missing: Vec<String>,
+}
 
 fn main() {
-    let input = include_str!("../../input/10-1.txt");
-    let result1 = day10::part1(input);
-    print!("part 1 {:?} ", result1);
-
-    println!();
-    let result2 = day10::part2(input);
-    print!("part 2 {} ", result2);
-    println!("");
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+    let solutions = match args.day {
+        Some(day) => match solution::get(day) {
+            Some(s) => vec![s],
+            None => {
+                eprintln!("Day {} isn't solved", day);
+                process::exit(1);
+            }
+        },
+        None => solution::all(),
+    };
+    let parts = match args.part {
+        Some(p) => vec![p],
+        None => vec![1, 2],
+    };
+    let mut known = match args.mode {
+        Mode::Run => Answers::default(),
+        _ => Answers::load(&args.answers).unwrap_or_else(|e| {
+            eprintln!("Can't load answers from {}: {}", args.answers, e);
+            process::exit(1);
+        }),
+    };
+
+    let start = Instant::now();
+    let mut report = Report::default();
+    for solution in solutions.iter() {
+        let day = solution.day();
+        let input = match read_input(day, args.input.as_deref()) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {:2}: can't read input: {}", day, e);
+                report
+                    .missing
+                    .extend(parts.iter().map(|part| format!("{:02}-{}", day, part)));
+                continue;
+            }
+        };
+        for (part, answer) in run(solution.as_ref(), &parts, &input) {
+            let key = format!("{:02}-{}", day, part);
+            match args.mode {
+                Mode::Run => (),
+                Mode::Check => match known.check(day, part, &answer) {
+                    Check::Match => report.matched += 1,
+                    Check::Mismatch { expected } => {
+                        println!("  WRONG: expected {}", expected);
+                        report.mismatched.push(key);
+                    }
+                    Check::Missing => {
+                        println!("  no answer on record");
+                        report.missing.push(key);
+                    }
+                },
+                Mode::Record => match known.check(day, part, &answer) {
+                    Check::Match => report.matched += 1,
+                    Check::Missing => {
+                        known.record(day, part, &answer);
+                        println!("  recorded");
+                    }
+                    Check::Mismatch { expected } if args.force => {
+                        known.record(day, part, &answer);
+                        println!("  replaced previous answer {}", expected);
+                    }
+                    Check::Mismatch { expected } => {
+                        println!("  differs from recorded answer {}, kept it", expected);
+                        report.mismatched.push(key);
+                    }
+                },
+            }
+        }
+    }
+    if args.day.is_none() {
+        println!("Total: {:.2?}", start.elapsed());
+    }
+
+    match args.mode {
+        Mode::Run => (),
+        Mode::Check => {
+            println!(
+                "{} correct, {} wrong, {} missing",
+                report.matched,
+                report.mismatched.len(),
+                report.missing.len()
+            );
+            if !report.mismatched.is_empty() {
+                println!("Wrong: {}", report.mismatched.join(", "));
+            }
+            if !report.missing.is_empty() {
+                println!("Missing: {}", report.missing.join(", "));
+            }
+            if !report.mismatched.is_empty() || !report.missing.is_empty() {
+                process::exit(1);
+            }
+        }
+        Mode::Record => {
+            if let Err(e) = known.save(&args.answers) {
+                eprintln!("Can't save answers to {}: {}", args.answers, e);
+                process::exit(1);
+            }
+            println!("Answers saved to {}", args.answers);
+            if !report.missing.is_empty() {
+                println!("Not run: {}", report.missing.join(", "));
+            }
+            if !report.mismatched.is_empty() {
+                println!(
+                    "Not replaced, use --force to: {}",
+                    report.mismatched.join(", ")
+                );
+            }
+            if !report.mismatched.is_empty() || !report.missing.is_empty() {
+                process::exit(1);
+            }
+        }
+    }
 }
@@ -0,0 +1,130 @@
+//! A common interface over every day's solution, so they can be run
+//! without knowing what type each of their answers has.
+
+use std::fmt;
+
+use crate::*;
+
+/// The answer to a single part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    Text(String),
+    /// The part has no answer of its own, like the second half of day
+    /// 25.
+    None,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(i) => write!(f, "{}", i),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(i: $t) -> Self {
+                Answer::Int(i as i128)
+            }
+        })*
+    };
+}
+
+answer_from_int!(i32, u32, i64, u64, usize, i128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+pub trait Solution {
+    fn day(&self) -> u32;
+    fn part1(&self, input: &str) -> Answer;
+    fn part2(&self, input: &str) -> Answer;
+
+    /// Runs part 1 or 2 of the puzzle.
+    fn part(&self, part: u32, input: &str) -> Answer {
+        match part {
+            1 => self.part1(input),
+            2 => self.part2(input),
+            _ => panic!("Puzzles only have parts 1 and 2, not {}", part),
+        }
+    }
+}
+
+/// Implements `Solution` for a new unit struct named `$name`, using
+/// the `part1` and `part2` functions of the module it's called from.
+/// Days without a second part can leave it out.
+macro_rules! impl_solution {
+    ($name:ident, $day:expr) => {
+        impl_solution!($name, $day, |input| part2(input).into());
+    };
+    ($name:ident, $day:expr, no_part2) => {
+        impl_solution!($name, $day, |_| $crate::solution::Answer::None);
+    };
+    ($name:ident, $day:expr, $part2:expr) => {
+        pub struct $name;
+
+        impl $crate::solution::Solution for $name {
+            fn day(&self) -> u32 {
+                $day
+            }
+
+            fn part1(&self, input: &str) -> $crate::solution::Answer {
+                part1(input).into()
+            }
+
+            fn part2(&self, input: &str) -> $crate::solution::Answer {
+                let run: fn(&str) -> $crate::solution::Answer = $part2;
+                run(input)
+            }
+        }
+    };
+}
+
+/// Every solved day, in order.
+pub fn all() -> Vec<Box<dyn Solution>> {
+    vec![
+        Box::new(day1::Day1),
+        Box::new(day2::Day2),
+        Box::new(day3::Day3),
+        Box::new(day4::Day4),
+        Box::new(day5::Day5),
+        Box::new(day6::Day6),
+        Box::new(day7::Day7),
+        Box::new(day8::Day8),
+        Box::new(day9::Day9),
+        Box::new(day10::Day10),
+        Box::new(day11::Day11),
+        Box::new(day12::Day12),
+        Box::new(day13::Day13),
+        Box::new(day14::Day14),
+        Box::new(day15::Day15),
+        Box::new(day16::Day16),
+        Box::new(day17::Day17),
+        Box::new(day18::Day18),
+        Box::new(day19::Day19),
+        Box::new(day20::Day20),
+        Box::new(day21::Day21),
+        Box::new(day22::Day22),
+        Box::new(day23::Day23),
+        Box::new(day24::Day24),
+        Box::new(day25::Day25),
+    ]
+}
+
+pub fn get(day: u32) -> Option<Box<dyn Solution>> {
+    all().into_iter().find(|s| s.day() == day)
+}
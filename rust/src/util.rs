@@ -1,139 +1,120 @@
+pub mod direction;
+pub mod grid;
+pub mod num;
+pub mod ocr;
+pub mod pnm;
+pub mod point;
+pub mod search;
+
+pub use direction::{Direction, Direction8, Turn};
+pub use grid::Grid;
+pub use point::{Coord, Point, Point3};
+
 use std::{
     iter::Extend,
-    ops::{Add, Index, IndexMut, Sub},
+    ops::{Index, IndexMut},
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Point {
-    pub x: i32,
-    pub y: i32,
+/// A very simple implementation of a matrix abstraction. It's
+/// simply a Vec an associated 'width' that is used for indexing.
+///
+/// Elements can be pushed one at a time, so the last row may be
+/// incomplete while the matrix is being filled. It still counts
+/// towards the height, and the missing elements are simply not there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    data: Vec<T>,
+    width: usize,
 }
 
-impl Point {
-    pub fn new(x: i32, y: i32) -> Self {
-        Point { x, y }
+impl<T> Matrix<T> {
+    /// Wraps a Vec of rows laid out one after the other, where the
+    /// last one may be incomplete just like when pushing. Panics if the
+    /// width is zero.
+    pub fn wrap(inner: Vec<T>, width: usize) -> Self {
+        assert!(width > 0, "Matrix width must not be zero");
+        Matrix { data: inner, width }
     }
-}
 
-impl Add<Point> for Point {
-    type Output = Point;
-    fn add(self, other: Point) -> Self::Output {
-        Point::new(self.x + other.x, self.y + other.y)
+    /// Creates a width*height Matrix with each element given by `f(x, y)`.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Matrix::wrap(data, width)
     }
-}
-impl Sub<Point> for Point {
-    type Output = Point;
-    fn sub(self, other: Point) -> Self::Output {
-        Point::new(self.x - other.x, self.y - other.y)
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
 
-impl Direction {
-    pub fn turn_right(&mut self) {
-        use Direction::*;
-        *self = match *self {
-            Up => Right,
-            Down => Left,
-            Left => Up,
-            Right => Down,
+    /// Returns an empty Matrix with memory allocated for width*height
+    /// elements.
+    pub fn with_capacity(width: usize, height: usize) -> Matrix<T> {
+        assert!(width > 0, "Matrix width must not be zero");
+        Matrix {
+            data: Vec::with_capacity(width * height),
+            width,
         }
     }
-    pub fn turn_left(&mut self) {
-        use Direction::*;
-        *self = match *self {
-            Up => Left,
-            Down => Right,
-            Left => Down,
-            Right => Up,
-        }
+
+    pub fn width(&self) -> usize {
+        self.width
     }
 
-    pub fn tuple(&self) -> (i32, i32) {
-        use Direction::*;
-        match *self {
-            Up => (0, -1),
-            Down => (0, 1),
-            Left => (-1, 0),
-            Right => (1, 0),
+    pub fn height(&self) -> usize {
+        self.data.len().div_ceil(self.width)
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        let i = y * self.width + x;
+        if x < self.width && i < self.data.len() {
+            Some(i)
+        } else {
+            None
         }
     }
-}
 
-pub fn gcd(mut a: i64, mut b: i64) -> i64 {
-    while b != 0 {
-        let old_b = b;
-        b = a % b;
-        a = old_b;
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.data[i])
     }
-    a
-}
 
-/// Computers the modular inverse of a mod b by Extended Euclidean algorithm.
-/// Panics if it doesn't exist.
-///
-/// Based on https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm#Modular_integers
-pub fn modular_inverse(a: i64, b: i64) -> i64 {
-    let (mut t_k, mut t_next) = (0,1);
-    let (mut r_k, mut r_next) = (b,a);
-    while r_next != 0 {
-        let quot = r_k / r_next;
-        let temp_r = r_k - quot * r_next;
-        let temp_t = t_k - quot * t_next;
-        r_k = r_next;
-        r_next = temp_r;
-        t_k = t_next;
-        t_next = temp_t;
-        
-    }
-    if r_k > 1 {
-        panic!("No modular inverse could be found");
-    }
-    if t_k < 0 {
-        t_k += b;
-    }
-    t_k
-}
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(move |i| &mut self.data[i])
+    }
 
-pub fn lcm(a: i64, b: i64) -> i64 {
-    (a * b).abs() / gcd(a, b)
-}
-/// A very simple implementation of a matrix abstraction. It's
-/// simply a Vec an associated 'width' that is used for indexing.
-#[derive(Debug)]
-pub struct Matrix<T> {
-    data: Vec<T>,
-    width: usize,
-}
+    pub fn row(&self, i: usize) -> &[T] {
+        let start = i * self.width;
+        &self.data[start..self.data.len().min(start + self.width)]
+    }
 
-impl<T> Matrix<T> {
-    pub fn wrap(inner: Vec<T>, width: usize) -> Self {
-        Matrix { data: inner, width }
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        let start = i * self.width;
+        let end = self.data.len().min(start + self.width);
+        &mut self.data[start..end]
     }
-    
-    pub fn width(&self) -> usize {
-        self.width
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.data.chunks(self.width)
     }
 
-    pub fn height(&self) -> usize {
-        self.data.len() / self.width
+    /// The elements of column `i`, from top to bottom.
+    pub fn column(&self, i: usize) -> impl Iterator<Item = &T> {
+        assert!(i < self.width, "Column {} is out of bounds", i);
+        self.data.iter().skip(i).step_by(self.width)
     }
-    pub fn row(&self, i: usize) -> &[T] {
-        return &self.data[(i * self.width)..(i * self.width + self.width)];
+
+    /// Iterates over every element along with its `(x, y)` position,
+    /// row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.data
+            .iter()
+            .enumerate()
+            .map(move |(i, e)| ((i % width, i / width), e))
     }
 
-    /// Returns a Matrix with memory allocated for size*size elements.
-    pub fn with_capacity(size: usize) -> Matrix<T> {
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Matrix<U> {
         Matrix {
-            data: Vec::with_capacity(size * size),
-            width: size,
+            data: self.data.iter().map(f).collect(),
+            width: self.width,
         }
     }
 
@@ -149,13 +130,20 @@ impl<T> Matrix<T> {
 }
 
 impl<T: Clone> Matrix<T> {
-    /// Creates a size*size Matrix with the given initial element
+    /// Creates a width*height Matrix with the given initial element
     /// occupying all positions.
-    pub fn with_element(size: usize, element: T) -> Matrix<T> {
-        Matrix {
-            data: vec![element; size * size],
-            width: size,
-        }
+    pub fn with_element(width: usize, height: usize, element: T) -> Matrix<T> {
+        Matrix::wrap(vec![element; width * height], width)
+    }
+
+    /// Swaps rows and columns. Panics if the last row is incomplete.
+    pub fn transpose(&self) -> Matrix<T> {
+        assert!(
+            self.data.len() % self.width == 0,
+            "Can't transpose a matrix with an incomplete row"
+        );
+        let height = self.height();
+        Matrix::from_fn(height, self.width, |x, y| self[(y, x)].clone())
     }
 }
 
@@ -163,15 +151,19 @@ impl<T> Index<(usize, usize)> for Matrix<T> {
     type Output = T;
 
     fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        let i = y * self.width + x;
-        return &self.data[i];
+        match self.index_of(x, y) {
+            Some(i) => &self.data[i],
+            None => panic!("({}, {}) is out of bounds", x, y),
+        }
     }
 }
 
 impl<T> IndexMut<(usize, usize)> for Matrix<T> {
     fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        let i = y * self.width + x;
-        return &mut self.data[i];
+        match self.index_of(x, y) {
+            Some(i) => &mut self.data[i],
+            None => panic!("({}, {}) is out of bounds", x, y),
+        }
     }
 }
 
@@ -182,3 +174,41 @@ impl<T> Extend<T> for Matrix<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix() {
+        let mut m = Matrix::from_fn(3, 2, |x, y| x + 10 * y);
+        assert_eq!((3, 2), (m.width(), m.height()));
+        assert_eq!(Some(&12), m.get(2, 1));
+        assert_eq!(None, m.get(3, 0));
+        assert_eq!(None, m.get(0, 2));
+        assert_eq!(vec![&1, &11], m.column(1).collect::<Vec<_>>());
+        m.row_mut(0)[1] = 5;
+        assert_eq!(&[0, 5, 2], m.row(0));
+        assert_eq!(Some(((2, 1), &12)), m.iter().last());
+
+        let t = m.transpose();
+        assert_eq!((2, 3), (t.width(), t.height()));
+        assert_eq!(&[5, 11], t.row(1));
+        assert_eq!(m, t.transpose());
+        let small = m.map(|&e| e < 10);
+        assert_eq!(vec![&true, &false], small.column(0).collect::<Vec<_>>());
+
+        m.push(20);
+        assert_eq!(3, m.height());
+        assert_eq!(&[20], m.row(2));
+        assert_eq!(None, m.get(1, 2));
+        assert_eq!(m, Matrix::wrap(m.inner().to_vec(), 3));
+        assert_eq!(3, m.rows().count());
+    }
+
+    #[test]
+    #[should_panic(expected = "incomplete row")]
+    fn test_transpose_incomplete() {
+        Matrix::wrap(vec![1, 2, 3], 2).transpose();
+    }
+}
@@ -0,0 +1,309 @@
+//! Directions on a grid where y grows downwards, and the turns between
+//! them.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Which way to turn to face a new direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirectionError(pub String);
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is not a direction", self.0)
+    }
+}
+
+impl std::error::Error for ParseDirectionError {}
+
+impl Direction {
+    /// All four directions, clockwise starting from `Up`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn all() -> impl Iterator<Item = Direction> {
+        Direction::ALL.iter().copied()
+    }
+
+    /// Reads a direction written as `U`/`D`/`L`/`R`, as a compass
+    /// point `N`/`S`/`W`/`E`, or as an arrow `^`/`v`/`<`/`>`. Letters
+    /// can be either case.
+    pub fn from_char(c: char) -> Option<Direction> {
+        use Direction::*;
+        match c.to_ascii_uppercase() {
+            'U' | 'N' | '^' => Some(Up),
+            'D' | 'S' | 'V' => Some(Down),
+            'L' | 'W' | '<' => Some(Left),
+            'R' | 'E' | '>' => Some(Right),
+            _ => None,
+        }
+    }
+
+    /// The arrow pointing this way, as drawn by the vacuum robot's
+    /// camera in day 17.
+    pub fn arrow(self) -> char {
+        use Direction::*;
+        match self {
+            Up => '^',
+            Down => 'v',
+            Left => '<',
+            Right => '>',
+        }
+    }
+
+    /// The movement command for this direction understood by Intcode
+    /// droids like the one in day 15: 1 for north, 2 for south, 3 for
+    /// west and 4 for east.
+    pub fn intcode(self) -> i64 {
+        use Direction::*;
+        match self {
+            Up => 1,
+            Down => 2,
+            Left => 3,
+            Right => 4,
+        }
+    }
+
+    pub fn from_intcode(code: i64) -> Option<Direction> {
+        use Direction::*;
+        match code {
+            1 => Some(Up),
+            2 => Some(Down),
+            3 => Some(Left),
+            4 => Some(Right),
+            _ => None,
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        use Direction::*;
+        match self {
+            Up => Down,
+            Down => Up,
+            Left => Right,
+            Right => Left,
+        }
+    }
+
+    pub fn turn_right(&mut self) {
+        use Direction::*;
+        *self = match *self {
+            Up => Right,
+            Down => Left,
+            Left => Up,
+            Right => Down,
+        }
+    }
+
+    pub fn turn_left(&mut self) {
+        use Direction::*;
+        *self = match *self {
+            Up => Left,
+            Down => Right,
+            Left => Down,
+            Right => Up,
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+            Turn::Around => *self = self.opposite(),
+        }
+    }
+
+    /// The direction faced after turning.
+    pub fn turned(mut self, turn: Turn) -> Direction {
+        self.turn(turn);
+        self
+    }
+
+    /// How to turn from this direction to face `other`, or `None` if
+    /// it's already facing it.
+    pub fn turn_to(self, other: Direction) -> Option<Turn> {
+        [Turn::Left, Turn::Right, Turn::Around]
+            .iter()
+            .copied()
+            .find(|&t| self.turned(t) == other)
+    }
+
+    pub fn tuple(&self) -> (i32, i32) {
+        use Direction::*;
+        match *self {
+            Up => (0, -1),
+            Down => (0, 1),
+            Left => (-1, 0),
+            Right => (1, 0),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next().and_then(Direction::from_char), chars.next()) {
+            (Some(dir), None) => Ok(dir),
+            _ => Err(ParseDirectionError(s.to_string())),
+        }
+    }
+}
+
+impl Turn {
+    /// The letter used for this turn in movement routines like day
+    /// 17's. Turning around has no letter of its own.
+    pub fn letter(self) -> Option<char> {
+        match self {
+            Turn::Left => Some('L'),
+            Turn::Right => Some('R'),
+            Turn::Around => None,
+        }
+    }
+}
+
+/// The four directions along with the diagonals between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// All eight directions, clockwise starting from `Up`.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up,
+        Direction8::UpRight,
+        Direction8::Right,
+        Direction8::DownRight,
+        Direction8::Down,
+        Direction8::DownLeft,
+        Direction8::Left,
+        Direction8::UpLeft,
+    ];
+
+    pub fn all() -> impl Iterator<Item = Direction8> {
+        Direction8::ALL.iter().copied()
+    }
+
+    fn index(self) -> usize {
+        Direction8::ALL.iter().position(|&d| d == self).unwrap()
+    }
+
+    pub fn opposite(self) -> Direction8 {
+        Direction8::ALL[(self.index() + 4) % 8]
+    }
+
+    /// The next direction clockwise, 45 degrees away.
+    pub fn clockwise(self) -> Direction8 {
+        Direction8::ALL[(self.index() + 1) % 8]
+    }
+
+    /// The next direction counterclockwise, 45 degrees away.
+    pub fn counterclockwise(self) -> Direction8 {
+        Direction8::ALL[(self.index() + 7) % 8]
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    pub fn tuple(&self) -> (i32, i32) {
+        use Direction8::*;
+        match *self {
+            Up => (0, -1),
+            UpRight => (1, -1),
+            Right => (1, 0),
+            DownRight => (1, 1),
+            Down => (0, 1),
+            DownLeft => (-1, 1),
+            Left => (-1, 0),
+            UpLeft => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(dir: Direction) -> Self {
+        match dir {
+            Direction::Up => Direction8::Up,
+            Direction::Down => Direction8::Down,
+            Direction::Left => Direction8::Left,
+            Direction::Right => Direction8::Right,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for (s, dir) in [
+            ("U", Direction::Up),
+            ("s", Direction::Down),
+            ("<", Direction::Left),
+        ]
+        .iter()
+        {
+            assert_eq!(Ok(*dir), s.parse());
+        }
+        assert_eq!(Some(Direction::Down), Direction::from_char('v'));
+        assert_eq!(Some(Direction::Right), Direction::from_char('E'));
+        assert!("X".parse::<Direction>().is_err());
+        assert!("UU".parse::<Direction>().is_err());
+        assert!("".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn test_turns() {
+        for dir in Direction::all() {
+            assert_eq!(Some(dir), Direction::from_intcode(dir.intcode()));
+            assert_eq!(Some(dir), Direction::from_char(dir.arrow()));
+            assert_eq!(dir, dir.opposite().opposite());
+            assert_eq!(None, dir.turn_to(dir));
+            assert_eq!(Some(Turn::Around), dir.turn_to(dir.opposite()));
+        }
+        assert_eq!(Some(Turn::Left), Direction::Up.turn_to(Direction::Left));
+        assert_eq!(Some(Turn::Right), Direction::Left.turn_to(Direction::Up));
+        assert_eq!(Direction::Down, Direction::Right.turned(Turn::Right));
+    }
+
+    #[test]
+    fn test_direction8() {
+        assert_eq!(8, Direction8::all().count());
+        assert_eq!(4, Direction8::all().filter(|d| d.is_diagonal()).count());
+        assert_eq!(Direction8::DownLeft, Direction8::UpRight.opposite());
+        assert_eq!(Direction8::Up, Direction8::UpLeft.clockwise());
+        assert_eq!(Direction8::UpLeft, Direction8::Up.counterclockwise());
+        for dir in Direction::all() {
+            assert_eq!(dir.tuple(), Direction8::from(dir).tuple());
+        }
+    }
+}
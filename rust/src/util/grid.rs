@@ -0,0 +1,287 @@
+//! A rectangular grid addressed by signed coordinates.
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use super::{Direction, Direction8, Point};
+
+/// A grid of `width * height` cells with (0, 0) at the top left, x
+/// growing to the right and y growing down. Coordinates are signed so
+/// that looking around the edges never underflows: positions outside
+/// the grid simply aren't in it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Wraps cells given in row-major order. Panics if there aren't
+    /// exactly `width * height` of them.
+    pub fn new(cells: Vec<T>, width: usize, height: usize) -> Self {
+        assert_eq!(
+            width * height,
+            cells.len(),
+            "A {}x{} grid needs {} cells",
+            width,
+            height,
+            width * height
+        );
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Parses a grid drawn as text, one row per line, turning each char
+    /// into a cell with `f`. Empty lines are skipped, and lines shorter
+    /// than the longest one are padded with spaces.
+    pub fn parse(s: &str, f: impl FnMut(char) -> T) -> Self {
+        let lines: Vec<&str> = s.lines().filter(|l| !l.is_empty()).collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let cells = lines
+            .iter()
+            .flat_map(|l| l.chars().chain(std::iter::repeat(' ')).take(width))
+            .map(f)
+            .collect();
+        Grid::new(cells, width, lines.len())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, Point { x, y }: Point) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    fn index_of(&self, pos: Point) -> Option<usize> {
+        if self.contains(pos) {
+            Some(pos.y as usize * self.width + pos.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        self.index_of(pos).map(move |i| &mut self.cells[i])
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// All cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Every position in the grid, in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let (width, height) = (self.width as i32, self.height as i32);
+        (0..height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    /// Every cell along with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The up to 4 positions next to `pos` that are in the grid,
+    /// clockwise starting from the one above.
+    pub fn neighbors4(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::all()
+            .map(move |d| pos + d)
+            .filter(move |&p| self.contains(p))
+    }
+
+    /// The up to 8 positions next to or diagonal from `pos` that are
+    /// in the grid, clockwise starting from the one above.
+    pub fn neighbors8(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction8::all()
+            .map(move |d| pos + d)
+            .filter(move |&p| self.contains(p))
+    }
+
+    /// Position of the first cell, in row-major order, for which `pred`
+    /// is true.
+    pub fn position(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Point> {
+        self.iter().find(|(_, c)| pred(c)).map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.cells.iter().map(f).collect(), self.width, self.height)
+    }
+
+    /// Draws the grid as text, one line per row, turning each cell into
+    /// a char with `f`.
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for y in 0..self.height {
+            s.extend(self.row(y).iter().map(&mut f));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first cell equal to `value`.
+    pub fn find(&self, value: &T) -> Option<Point> {
+        self.position(|c| c == value)
+    }
+
+    /// Positions of every cell equal to `value`, in row-major order.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a {
+        self.iter()
+            .filter(move |(_, c)| *c == value)
+            .map(|(pos, _)| pos)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid with every cell set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Grid::new(vec![value; width * height], width, height)
+    }
+
+    /// Builds a new grid of the given size, taking cell (x, y) from
+    /// wherever `source` says in this one.
+    fn remap(&self, width: usize, height: usize, source: impl Fn(i32, i32) -> Point) -> Self {
+        let cells = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| self[source(x, y)].clone())
+            .collect();
+        Grid::new(cells, width, height)
+    }
+
+    /// Rotates the grid 90 degrees clockwise.
+    pub fn rotate_right(&self) -> Self {
+        let h = self.height as i32;
+        self.remap(self.height, self.width, |x, y| Point::new(y, h - 1 - x))
+    }
+
+    /// Rotates the grid 90 degrees counterclockwise.
+    pub fn rotate_left(&self) -> Self {
+        let w = self.width as i32;
+        self.remap(self.height, self.width, |x, y| Point::new(w - 1 - y, x))
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        let w = self.width as i32;
+        self.remap(self.width, self.height, |x, y| Point::new(w - 1 - x, y))
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        let h = self.height as i32;
+        self.remap(self.width, self.height, |x, y| Point::new(x, h - 1 - y))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point) -> &Self::Output {
+        match self.index_of(pos) {
+            Some(i) => &self.cells[i],
+            None => panic!("{} is outside the {}x{} grid", pos, self.width, self.height),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, pos: Point) -> &mut Self::Output {
+        match self.index_of(pos) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("{} is outside the {}x{} grid", pos, self.width, self.height),
+        }
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(|&c| c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "#..\n.#.\n##.\n.#.\n";
+
+    #[test]
+    fn test_parse_render() {
+        let grid = Grid::parse(TEXT, |c| c);
+        assert_eq!((3, 4), (grid.width(), grid.height()));
+        assert_eq!(Some(&'#'), grid.get(Point::new(1, 2)));
+        assert_eq!(None, grid.get(Point::new(3, 0)));
+        assert_eq!(None, grid.get(Point::new(0, -1)));
+        assert_eq!(TEXT, grid.to_string());
+        let padded = Grid::parse("\n#\n###\n", |c| c);
+        assert_eq!("#  \n###\n", padded.to_string());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::parse(TEXT, |c| c == '#');
+        let corner: Vec<_> = grid.neighbors4(Point::new(0, 0)).collect();
+        assert_eq!(vec![Point::new(1, 0), Point::new(0, 1)], corner);
+        assert_eq!(8, grid.neighbors8(Point::new(1, 1)).count());
+        assert_eq!(3, grid.neighbors8(Point::new(2, 3)).count());
+        let lit = grid
+            .neighbors8(Point::new(1, 2))
+            .filter(|&p| grid[p])
+            .count();
+        assert_eq!(3, lit);
+    }
+
+    #[test]
+    fn test_find() {
+        let grid = Grid::parse(TEXT, |c| c);
+        assert_eq!(Some(Point::new(0, 0)), grid.find(&'#'));
+        let all: Vec<_> = grid.find_all(&'#').collect();
+        assert_eq!(
+            vec![
+                Point::new(0, 0),
+                Point::new(1, 1),
+                Point::new(0, 2),
+                Point::new(1, 2),
+                Point::new(1, 3)
+            ],
+            all
+        );
+        assert_eq!(None, grid.find(&'x'));
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = Grid::parse(TEXT, |c| c);
+        assert_eq!(".#.#\n###.\n....\n", grid.rotate_right().to_string());
+        assert_eq!("....\n.###\n#.#.\n", grid.rotate_left().to_string());
+        assert_eq!("..#\n.#.\n.##\n.#.\n", grid.flip_horizontal().to_string());
+        assert_eq!(".#.\n##.\n.#.\n#..\n", grid.flip_vertical().to_string());
+        assert_eq!(grid, grid.rotate_right().rotate_left());
+        assert_eq!(
+            grid,
+            grid.rotate_right()
+                .rotate_right()
+                .rotate_right()
+                .rotate_right()
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Number theory helpers: gcd and lcm, modular arithmetic and the
+//! Chinese remainder theorem.
+//!
+//! Modular functions take the modulus last and always return values in
+//! `0..m`, even for negative inputs. The modulus must be positive: the
+//! ones returning an `Option` give `None` otherwise, and the rest panic. The ones working on `i128` are
+//! safe for any modulus up to `i128::MAX`, not only those whose squares
+//! fit.
+
+use std::convert::TryFrom;
+
+/// Greatest common divisor of `a` and `b`. It's unsigned because the
+/// gcd of `i64::MIN` and 0 doesn't fit in an `i64`.
+pub fn gcd(a: i64, b: i64) -> u64 {
+    gcd_u64(a.unsigned_abs(), b.unsigned_abs())
+}
+
+pub fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let old_b = b;
+        b = a % b;
+        a = old_b;
+    }
+    a
+}
+
+/// Returns `(g, x, y)` where `g` is the gcd of `a` and `b` and
+/// `a * x + b * y == g`.
+///
+/// Based on https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r_k, mut r_next) = (a, b);
+    let (mut s_k, mut s_next) = (1, 0);
+    let (mut t_k, mut t_next) = (0, 1);
+    while r_next != 0 {
+        let quot = r_k / r_next;
+        let r = r_k - quot * r_next;
+        r_k = r_next;
+        r_next = r;
+        let s = s_k - quot * s_next;
+        s_k = s_next;
+        s_next = s;
+        let t = t_k - quot * t_next;
+        t_k = t_next;
+        t_next = t;
+    }
+    if r_k < 0 {
+        (-r_k, -s_k, -t_k)
+    } else {
+        (r_k, s_k, t_k)
+    }
+}
+
+/// The `x` in `0..m` for which `a * x` is 1 mod `m`, if `a` and `m`
+/// are coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g == 1 {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+pub fn mulmod_u64(a: u64, b: u64, m: u64) -> u64 {
+    assert!(m > 0, "Modulus must be positive");
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+pub fn powmod_u64(base: u64, mut exp: u64, m: u64) -> u64 {
+    assert!(m > 0, "Modulus must be positive");
+    let mut base = base % m;
+    let mut r = 1 % m;
+    while exp > 0 {
+        if exp % 2 == 1 {
+            r = mulmod_u64(r, base, m);
+        }
+        exp >>= 1;
+        base = mulmod_u64(base, base, m);
+    }
+    r
+}
+
+/// `a * b` mod `m`. Multiplies directly when that can't overflow, and
+/// falls back to adding up doublings of `a` when it can.
+pub fn mulmod(a: i128, b: i128, m: i128) -> i128 {
+    assert!(m > 0, "Modulus must be positive");
+    let (mut a, mut b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    let mut r = 0;
+    while b > 0 {
+        if b % 2 == 1 {
+            r = addmod(r, a, m);
+        }
+        a = addmod(a, a, m);
+        b >>= 1;
+    }
+    r
+}
+
+/// `a + b` mod `m`, for `a` and `b` already in `0..m`.
+fn addmod(a: i128, b: i128, m: i128) -> i128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// `base` to the power of `exp` mod `m`, by squaring.
+pub fn powmod(base: i128, mut exp: u128, m: i128) -> i128 {
+    assert!(m > 0, "Modulus must be positive");
+    let mut base = base.rem_euclid(m);
+    let mut r = 1 % m;
+    while exp > 0 {
+        if exp % 2 == 1 {
+            r = mulmod(r, base, m);
+        }
+        exp >>= 1;
+        base = mulmod(base, base, m);
+    }
+    r
+}
+
+/// Solves a system of congruences `x = r (mod m)` given as `(r, m)`
+/// pairs, returning `(x, l)` where `l` is the lcm of the moduli and
+/// `x` is the only solution in `0..l`. The moduli don't need to be
+/// coprime, but there's no solution when they share a factor the
+/// remainders disagree on, when `l` doesn't fit, or when any modulus
+/// isn't positive.
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    let (mut x, mut l) = (0, 1);
+    for (r, m) in congruences {
+        if m <= 0 {
+            return None;
+        }
+        let (g, p, _) = extended_gcd(l, m);
+        let diff = r - x;
+        if diff % g != 0 {
+            return None;
+        }
+        // x + l * k satisfies both for k = diff / g * p mod m / g
+        let step = m / g;
+        let k = mulmod(diff / g, p, step);
+        let new_l = l.checked_mul(step)?;
+        x = (x + mulmod(l, k, new_l)).rem_euclid(new_l);
+        l = new_l;
+    }
+    Some((x, l))
+}
+
+/// Least common multiple of `a` and `b`, or `None` if it overflows.
+pub fn checked_lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let lcm = (a.unsigned_abs() / gcd(a, b)).checked_mul(b.unsigned_abs())?;
+    i64::try_from(lcm).ok()
+}
+
+/// Least common multiple of every number, or `None` if it overflows.
+/// The lcm of no numbers is 1.
+pub fn lcm_all(numbers: impl IntoIterator<Item = i64>) -> Option<i64> {
+    numbers.into_iter().try_fold(1, checked_lcm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(6, gcd(-12, 18));
+        assert_eq!(5, gcd(0, -5));
+        assert_eq!(3, gcd_u64(u64::MAX, 6));
+        for &(a, b) in [(240, 46), (-7, 3), (0, 9), (12, -18)].iter() {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(gcd(a as i64, b as i64) as i128, g);
+            assert_eq!(g, a * x + b * y);
+        }
+        assert_eq!(Some(60), lcm_all(vec![4, 6, 10, 15]));
+        assert_eq!(Some(1), lcm_all(None));
+        assert_eq!(None, checked_lcm(i64::MAX, i64::MAX - 1));
+
+        // |i64::MIN| doesn't fit in an i64
+        assert_eq!(1 << 63, gcd(i64::MIN, 0));
+        assert_eq!(1, gcd(i64::MIN, i64::MAX));
+        assert_eq!(None, checked_lcm(i64::MIN, 1));
+        assert_eq!(None, checked_lcm(i64::MIN, i64::MIN));
+        assert_eq!(None, lcm_all(vec![3, i64::MIN]));
+        assert_eq!(Some(i64::MAX), checked_lcm(-i64::MAX, 1));
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(6, 9));
+        assert_eq!(445, powmod(4, 13, 497));
+        assert_eq!(445, powmod_u64(4, 13, 497));
+        assert_eq!(0, powmod(5, 0, 1));
+
+        // big enough that the product doesn't fit in an i128
+        let m = (1 << 126) + 1;
+        let a = m - 2;
+        assert_eq!(4, mulmod(a, a, m));
+        assert_eq!(m - 4, mulmod(a, 2, m));
+        assert_eq!(1, mulmod_u64(u64::MAX - 1, u64::MAX - 1, u64::MAX));
+        let p = 1_000_000_007;
+        assert_eq!(1, powmod(123_456_789, p as u128 - 1, p));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((23, 105)), crt(vec![(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((10, 12)), crt(vec![(2, 4), (4, 6)]));
+        assert_eq!(None, crt(vec![(1, 4), (2, 6)]));
+        assert_eq!(Some((0, 1)), crt(None));
+        assert_eq!(Some((3, 7)), crt(vec![(-4, 7)]));
+        assert_eq!(None, crt(vec![(2, 3), (1, 0)]));
+        assert_eq!(None, crt(vec![(1, -5)]));
+    }
+
+    #[test]
+    fn test_bad_modulus() {
+        assert_eq!(None, mod_inverse(3, 0));
+        assert_eq!(None, mod_inverse(3, -11));
+        for m in [0, -7].iter().copied() {
+            assert!(std::panic::catch_unwind(|| mulmod(2, 3, m)).is_err());
+            assert!(std::panic::catch_unwind(|| powmod(2, 3, m)).is_err());
+        }
+        assert!(std::panic::catch_unwind(|| powmod_u64(2, 3, 0)).is_err());
+    }
+}
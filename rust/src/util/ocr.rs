@@ -0,0 +1,190 @@
+//! Reads the block letters some puzzles draw as their answer, like
+//!
+//! ```text
+//! .##..###.
+//! #..#.#..#
+//! #..#.###.
+//! ####.#..#
+//! #..#.#..#
+//! #..#.###.
+//! ```
+//!
+//! Letters are 6 pixels tall and usually 4 wide, with at least one
+//! empty column between them. Only the letters that have shown up in
+//! puzzles so far are known.
+
+use std::fmt;
+
+use super::Matrix;
+
+const HEIGHT: usize = 6;
+
+const GLYPHS: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// Letters are always 6 pixels tall.
+    WrongHeight(usize),
+    /// Nothing in the alphabet matches the pixels starting at this
+    /// column. The glyph is drawn up to the next empty column.
+    UnknownGlyph { column: usize, glyph: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::WrongHeight(h) => {
+                write!(f, "letters are {} pixels tall, found {}", HEIGHT, h)
+            }
+            OcrError::UnknownGlyph { column, glyph } => {
+                write!(f, "unknown glyph at column {}:\n{}", column, glyph)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Columns of a glyph, each one a bitmask with bit `y` set if the
+/// pixel in row `y` is lit.
+fn glyph_columns(glyph: &str) -> Vec<u8> {
+    let rows: Vec<&[u8]> = glyph.lines().map(str::as_bytes).collect();
+    (0..rows[0].len())
+        .map(|x| {
+            (0..HEIGHT)
+                .filter(|&y| rows[y][x] == b'#')
+                .fold(0, |acc, y| acc | (1 << y))
+        })
+        .collect()
+}
+
+fn draw(columns: &[u8]) -> String {
+    (0..HEIGHT)
+        .map(|y| {
+            columns
+                .iter()
+                .map(|c| if c & (1 << y) != 0 { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads the letters in `rows`, where `true` is a lit pixel. Empty
+/// columns before, between and after letters are skipped.
+pub fn recognize_rows<R: AsRef<[bool]>>(rows: &[R]) -> Result<String, OcrError> {
+    if rows.len() != HEIGHT {
+        return Err(OcrError::WrongHeight(rows.len()));
+    }
+    let width = rows.iter().map(|r| r.as_ref().len()).max().unwrap_or(0);
+    let columns: Vec<u8> = (0..width)
+        .map(|x| {
+            (0..HEIGHT)
+                .filter(|&y| *rows[y].as_ref().get(x).unwrap_or(&false))
+                .fold(0, |acc, y| acc | (1 << y))
+        })
+        .collect();
+    let mut glyphs: Vec<(char, Vec<u8>)> =
+        GLYPHS.iter().map(|&(c, g)| (c, glyph_columns(g))).collect();
+    // wider glyphs first, in case one starts like a narrower one and
+    // they're drawn without a gap after them
+    glyphs.sort_by_key(|(_, g)| std::cmp::Reverse(g.len()));
+
+    let mut text = String::new();
+    let mut x = 0;
+    while x < width {
+        if columns[x] == 0 {
+            x += 1;
+            continue;
+        }
+        let rest = &columns[x..];
+        match glyphs.iter().find(|(_, g)| rest.starts_with(g)) {
+            Some((c, g)) => {
+                text.push(*c);
+                x += g.len();
+            }
+            None => {
+                let len = rest.iter().position(|&c| c == 0).unwrap_or(rest.len());
+                return Err(OcrError::UnknownGlyph {
+                    column: x,
+                    glyph: draw(&rest[..len]),
+                });
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Same as `recognize_rows`, for a matrix of pixels.
+pub fn recognize(matrix: &Matrix<bool>) -> Result<String, OcrError> {
+    let rows: Vec<&[bool]> = matrix.rows().collect();
+    recognize_rows(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Vec<Vec<bool>> {
+        s.lines()
+            .map(|l| l.chars().map(|c| c == '#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let rows: Vec<Vec<bool>> = (0..HEIGHT)
+            .map(|y| {
+                let mut row = Vec::new();
+                for (_, glyph) in GLYPHS.iter() {
+                    row.extend(glyph.lines().nth(y).unwrap().chars().map(|c| c == '#'));
+                    row.push(false);
+                }
+                row
+            })
+            .collect();
+        let letters: String = GLYPHS.iter().map(|(c, _)| c).collect();
+        assert_eq!(Ok(letters), recognize_rows(&rows));
+    }
+
+    #[test]
+    fn test_padding() {
+        let rows = parse(
+            "...##..###...\n..#..#.#..#..\n..#..#.###...\n..####.#..#..\n..#..#.#..#..\n..#..#.###...",
+        );
+        assert_eq!(Ok("AB".to_string()), recognize_rows(&rows));
+    }
+
+    #[test]
+    fn test_errors() {
+        let rows = parse("#..#\n#..#\n####\n#..#\n#..#");
+        assert_eq!(Err(OcrError::WrongHeight(5)), recognize_rows(&rows));
+        let rows = parse("####.#..\n#..#.#..\n#..#.#..\n#..#.#..\n#..#.#..\n####.###");
+        assert_eq!(
+            Err(OcrError::UnknownGlyph {
+                column: 0,
+                glyph: "####\n#..#\n#..#\n#..#\n#..#\n####".to_string()
+            }),
+            recognize_rows(&rows)
+        );
+    }
+}
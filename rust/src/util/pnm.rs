@@ -0,0 +1,57 @@
+//! Writers for the simple [Netpbm](https://en.wikipedia.org/wiki/Netpbm)
+//! image formats, which are easy to produce by hand and can be opened
+//! by most image viewers.
+
+use std::io::{self, Write};
+
+/// Writes a binary (P6) PPM image. `pixels` are the RGB colors of the
+/// image in row-major order, and there should be exactly `width *
+/// height` of them.
+pub fn write_ppm<W, I>(w: &mut W, width: usize, height: usize, pixels: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = [u8; 3]>,
+{
+    write!(w, "P6\n{} {}\n255\n", width, height)?;
+    for pixel in pixels {
+        w.write_all(&pixel)?;
+    }
+    Ok(())
+}
+
+/// Writes a binary (P4) PBM image. `pixels` are `true` for black and
+/// `false` for white, in row-major order, and there should be exactly
+/// `width * height` of them.
+pub fn write_pbm<W, I>(w: &mut W, width: usize, height: usize, pixels: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = bool>,
+{
+    write!(w, "P4\n{} {}\n", width, height)?;
+    let mut pixels = pixels.into_iter();
+    // each row is packed into bytes on its own, padded with zeroes
+    let mut row = vec![0u8; width.div_ceil(8)];
+    for _ in 0..height {
+        row.iter_mut().for_each(|b| *b = 0);
+        for (x, black) in pixels.by_ref().take(width).enumerate() {
+            if black {
+                row[x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        w.write_all(&row)?;
+    }
+    Ok(())
+}
+
+/// Writes a binary (P5) PGM image with 8 bit gray levels, 0 being
+/// black. `pixels` are in row-major order, and there should be exactly
+/// `width * height` of them.
+pub fn write_pgm<W, I>(w: &mut W, width: usize, height: usize, pixels: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = u8>,
+{
+    write!(w, "P5\n{} {}\n255\n", width, height)?;
+    let pixels: Vec<u8> = pixels.into_iter().collect();
+    w.write_all(&pixels)
+}
@@ -0,0 +1,306 @@
+//! Points in 2D and 3D space, also used as the vectors between them.
+//!
+//! Both are generic over the signed integer types, defaulting to `i32`.
+//! In 2D, y grows downwards, matching the puzzles' maps and `Direction`.
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use super::{Direction, Direction8};
+
+/// The integer types points can be made of. Points double as the
+/// vectors between them, which need to be negated and to point left or
+/// up, so only the signed types are coordinates. Unsigned values, like
+/// `Grid` indices, are converted at the edges instead.
+pub trait Coord:
+    Copy
+    + Ord
+    + Default
+    + fmt::Debug
+    + fmt::Display
+    + From<i8>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(impl Coord for $t {
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn signum(self) -> Self {
+                <$t>::signum(self)
+            }
+        })*
+    };
+}
+
+impl_coord!(i8, i16, i32, i64, i128, isize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point<T = i32> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coord> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+
+    pub fn origin() -> Self {
+        Point::default()
+    }
+
+    pub fn manhattan(self, other: Self) -> T {
+        let d = self - other;
+        d.x.abs() + d.y.abs()
+    }
+
+    /// Distance when diagonal moves are allowed too.
+    pub fn chebyshev(self, other: Self) -> T {
+        let d = self - other;
+        d.x.abs().max(d.y.abs())
+    }
+
+    /// Rotates the point 90 degrees clockwise around the origin, so
+    /// `Direction::Up` becomes `Direction::Right`.
+    pub fn rotate_right(self) -> Self {
+        Point::new(-self.y, self.x)
+    }
+
+    /// Rotates the point 90 degrees counterclockwise around the origin.
+    pub fn rotate_left(self) -> Self {
+        Point::new(self.y, -self.x)
+    }
+
+    /// The sign of each coordinate.
+    pub fn signum(self) -> Self {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<T: Coord> From<Direction> for Point<T> {
+    fn from(dir: Direction) -> Self {
+        let (x, y) = dir.tuple();
+        Point::new(T::from(x as i8), T::from(y as i8))
+    }
+}
+
+impl<T: Coord> From<Direction8> for Point<T> {
+    fn from(dir: Direction8) -> Self {
+        let (x, y) = dir.tuple();
+        Point::new(T::from(x as i8), T::from(y as i8))
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(p: Point<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T: Coord> Add for Point<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+/// Takes one step in the given direction.
+impl<T: Coord> Add<Direction> for Point<T> {
+    type Output = Self;
+    fn add(self, dir: Direction) -> Self {
+        self + Point::from(dir)
+    }
+}
+
+impl<T: Coord> Add<Direction8> for Point<T> {
+    type Output = Self;
+    fn add(self, dir: Direction8) -> Self {
+        self + Point::from(dir)
+    }
+}
+
+impl<T: Coord> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coord> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Coord> AddAssign<Direction> for Point<T> {
+    fn add_assign(&mut self, dir: Direction) {
+        *self = *self + dir;
+    }
+}
+
+impl<T: Coord> Neg for Point<T> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Coord> Mul<T> for Point<T> {
+    type Output = Self;
+    fn mul(self, k: T) -> Self {
+        Point::new(self.x * k, self.y * k)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point3<T = i32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coord> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+
+    pub fn origin() -> Self {
+        Point3::default()
+    }
+
+    pub fn manhattan(self, other: Self) -> T {
+        let d = self - other;
+        d.x.abs() + d.y.abs() + d.z.abs()
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        let d = self - other;
+        d.x.abs().max(d.y.abs()).max(d.z.abs())
+    }
+
+    /// The sign of each coordinate.
+    pub fn signum(self) -> Self {
+        Point3::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Coord> Add for Point3<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Coord> Sub for Point3<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Coord> AddAssign for Point3<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coord> SubAssign for Point3<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Coord> Neg for Point3<T> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Coord> Mul<T> for Point3<T> {
+    type Output = Self;
+    fn mul(self, k: T) -> Self {
+        Point3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point() {
+        let p = Point::new(3, -4);
+        assert_eq!(7, p.manhattan(Point::origin()));
+        assert_eq!(4, p.chebyshev(Point::origin()));
+        assert_eq!(Point::new(-3, 4), -p);
+        assert_eq!(Point::new(6, -8), p * 2);
+        assert_eq!(Point::new(3, -5), p + Direction::Up);
+        assert_eq!(Point::new(2, -3), p + Direction8::DownLeft);
+        assert_eq!(Point::new(4, 3), p.rotate_right());
+        assert_eq!(Point::new(-4, -3), p.rotate_left());
+        assert_eq!(
+            p,
+            p.rotate_right()
+                .rotate_right()
+                .rotate_right()
+                .rotate_right()
+        );
+        let up: Point = Direction::Up.into();
+        assert_eq!(Point::from(Direction::Right), up.rotate_right());
+        let big: Point<i64> = Point::new(1 << 40, 0);
+        assert_eq!(1 << 41, big.manhattan(-big));
+    }
+
+    #[test]
+    fn test_point3() {
+        let p = Point3::new(-1, 2, -3);
+        assert_eq!(6, p.manhattan(Point3::origin()));
+        assert_eq!(3, p.chebyshev(Point3::origin()));
+        assert_eq!(Point3::new(-1, 1, -1), p.signum());
+        assert_eq!(Point3::new(0, 0, 0), p + -p);
+        assert_eq!(Point3::new(-2, 4, -6), p * 2);
+    }
+}
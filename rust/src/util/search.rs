@@ -0,0 +1,410 @@
+//! Graph searches over implicit graphs.
+//!
+//! Nothing here needs an explicit graph: states can be anything that
+//! can be hashed, and their neighbors are given by a function. Every
+//! search can start from several states at once, as if they were all
+//! one step away from a common start.
+//!
+//! `bfs` and `dijkstra` are iterators yielding each state reached along
+//! with its distance, closest first, so stopping early is a matter of
+//! stopping the iteration and finding the distance to everything is a
+//! matter of collecting it. When the way there matters too, the
+//! `_path` versions and `astar` return it along with its cost.
+
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use super::{Grid, Point};
+
+/// Keeps track of the states a search has already reached, so it
+/// doesn't go through them again. A `HashSet` works for anything, but
+/// other kinds can be faster or use less memory when the states allow
+/// it, like a `Grid<bool>` for positions on a grid.
+pub trait Visited<S> {
+    /// Marks `state` as visited, returning whether it wasn't already.
+    fn visit(&mut self, state: &S) -> bool;
+}
+
+impl<S: Hash + Eq + Clone> Visited<S> for HashSet<S> {
+    fn visit(&mut self, state: &S) -> bool {
+        self.insert(state.clone())
+    }
+}
+
+impl<S: Ord + Clone> Visited<S> for BTreeSet<S> {
+    fn visit(&mut self, state: &S) -> bool {
+        self.insert(state.clone())
+    }
+}
+
+/// Positions outside the grid are never visited.
+impl Visited<Point> for Grid<bool> {
+    fn visit(&mut self, state: &Point) -> bool {
+        match self.get_mut(*state) {
+            Some(seen @ false) => {
+                *seen = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Breadth-first search, yielding every state reachable from the
+/// starts with the number of steps needed to get there, in order of
+/// distance.
+pub struct Bfs<S, N, V> {
+    queue: VecDeque<(S, usize)>,
+    neighbors: N,
+    visited: V,
+}
+
+/// Starts a breadth-first search from `starts`, using a `HashSet` to
+/// track visited states.
+pub fn bfs<S, N, I>(starts: impl IntoIterator<Item = S>, neighbors: N) -> Bfs<S, N, HashSet<S>>
+where
+    S: Hash + Eq + Clone,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    bfs_with(starts, neighbors, HashSet::new())
+}
+
+/// Same as `bfs`, but tracking visited states with `visited`.
+pub fn bfs_with<S, N, I, V>(
+    starts: impl IntoIterator<Item = S>,
+    neighbors: N,
+    mut visited: V,
+) -> Bfs<S, N, V>
+where
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    V: Visited<S>,
+{
+    let queue = starts
+        .into_iter()
+        .filter(|s| visited.visit(s))
+        .map(|s| (s, 0))
+        .collect();
+    Bfs {
+        queue,
+        neighbors,
+        visited,
+    }
+}
+
+impl<S, N, I, V> Iterator for Bfs<S, N, V>
+where
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    V: Visited<S>,
+{
+    type Item = (S, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (state, dist) = self.queue.pop_front()?;
+        for next in (self.neighbors)(&state) {
+            if self.visited.visit(&next) {
+                self.queue.push_back((next, dist + 1));
+            }
+        }
+        Some((state, dist))
+    }
+}
+
+/// Shortest path from any of the starts to a state for which
+/// `is_goal` is true, including both ends, along with its number of
+/// steps.
+pub fn bfs_path<S, N, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbors: N,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(Vec<S>, usize)>
+where
+    S: Hash + Eq + Clone,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(e) = parents.entry(start.clone()) {
+            e.insert(None);
+            queue.push_back(start);
+        }
+    }
+    while let Some(state) = queue.pop_front() {
+        if is_goal(&state) {
+            let path = reconstruct(&parents, state);
+            let steps = path.len() - 1;
+            return Some((path, steps));
+        }
+        for next in neighbors(&state) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(state.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The costs search can work with, which only need to be added up and
+/// compared. Zero is their default value.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<C: Copy + Ord + Default + Add<Output = C>> Cost for C {}
+
+/// An entry in the search frontier, ordered so the cheapest one is the
+/// greatest, as `BinaryHeap` is a max-heap.
+struct Frontier<S, C> {
+    /// What's being ordered by: the cost so far, plus the estimate of
+    /// what's left for A*.
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Frontier<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S, C: Ord> Eq for Frontier<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Frontier<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Frontier<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Dijkstra's algorithm, yielding every state reachable from the
+/// starts with the cost of the cheapest way there, cheapest first.
+pub struct Dijkstra<S, C, N> {
+    heap: BinaryHeap<Frontier<S, C>>,
+    best: HashMap<S, C>,
+    settled: HashSet<S>,
+    neighbors: N,
+}
+
+/// Starts Dijkstra's algorithm from `starts`, where `neighbors` gives
+/// the states one move away from a state along with the cost of that
+/// move.
+pub fn dijkstra<S, C, N, I>(starts: impl IntoIterator<Item = S>, neighbors: N) -> Dijkstra<S, C, N>
+where
+    S: Hash + Eq + Clone,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut best = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        best.insert(start.clone(), C::default());
+        heap.push(Frontier {
+            priority: C::default(),
+            cost: C::default(),
+            state: start,
+        });
+    }
+    Dijkstra {
+        heap,
+        best,
+        settled: HashSet::new(),
+        neighbors,
+    }
+}
+
+impl<S, C, N, I> Iterator for Dijkstra<S, C, N>
+where
+    S: Hash + Eq + Clone,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+{
+    type Item = (S, C);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Frontier { cost, state, .. } = self.heap.pop()?;
+            // either a cheaper way here was found after this one was
+            // queued, or it was queued twice at the same cost
+            if cost > self.best[&state] || !self.settled.insert(state.clone()) {
+                continue;
+            }
+            for (next, step) in (self.neighbors)(&state) {
+                let next_cost = cost + step;
+                if self.best.get(&next).is_none_or(|&old| next_cost < old) {
+                    self.best.insert(next.clone(), next_cost);
+                    self.heap.push(Frontier {
+                        priority: next_cost,
+                        cost: next_cost,
+                        state: next,
+                    });
+                }
+            }
+            return Some((state, cost));
+        }
+    }
+}
+
+/// Cheapest path from any of the starts to a state for which `is_goal`
+/// is true, including both ends, along with its cost.
+pub fn dijkstra_path<S, C, N, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbors: N,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(Vec<S>, C)>
+where
+    S: Hash + Eq + Clone,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, neighbors, |_| C::default(), is_goal)
+}
+
+/// A* search for the cheapest path from any of the starts to a state
+/// for which `is_goal` is true, including both ends, along with its
+/// cost. `heuristic` estimates the cost from a state to the nearest
+/// goal, and the path found is only the cheapest if it never
+/// overestimates it.
+pub fn astar<S, C, N, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbors: N,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(Vec<S>, C)>
+where
+    S: Hash + Eq + Clone,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut best = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        best.insert(start.clone(), C::default());
+        parents.insert(start.clone(), None);
+        heap.push(Frontier {
+            priority: heuristic(&start),
+            cost: C::default(),
+            state: start,
+        });
+    }
+    while let Some(Frontier { cost, state, .. }) = heap.pop() {
+        if cost > best[&state] {
+            continue;
+        }
+        if is_goal(&state) {
+            return Some((reconstruct(&parents, state), cost));
+        }
+        for (next, step) in neighbors(&state) {
+            let next_cost = cost + step;
+            if best.get(&next).is_none_or(|&old| next_cost < old) {
+                best.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), Some(state.clone()));
+                heap.push(Frontier {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    state: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Follows the parents back from `end` to the start it was reached
+/// from.
+fn reconstruct<S: Hash + Eq + Clone>(parents: &HashMap<S, Option<S>>, end: S) -> Vec<S> {
+    let mut path = vec![end];
+    while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "\
+#########
+#S..#...#
+#.#.#.#.#
+#.#...#E#
+#########";
+
+    fn open_neighbors(grid: &Grid<char>) -> impl FnMut(&Point) -> Vec<Point> + '_ {
+        move |&p| grid.neighbors4(p).filter(|&n| grid[n] != '#').collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = Grid::parse(MAZE, |c| c);
+        let (start, end) = (grid.find(&'S').unwrap(), grid.find(&'E').unwrap());
+        let dist: HashMap<_, _> = bfs(Some(start), open_neighbors(&grid)).collect();
+        assert_eq!(Some(&12), dist.get(&end));
+        assert_eq!(15, dist.len());
+
+        let visited = grid.map(|_| false);
+        let found = bfs_with(Some(start), open_neighbors(&grid), visited).find(|&(p, _)| p == end);
+        assert_eq!(Some((end, 12)), found);
+
+        let (path, steps) = bfs_path(Some(start), open_neighbors(&grid), |&p| p == end).unwrap();
+        assert_eq!(12, steps);
+        assert_eq!((start, end), (path[0], path[12]));
+        assert!(path.windows(2).all(|w| w[0].manhattan(w[1]) == 1));
+
+        // from both ends, every tile is at most 6 steps away
+        let far = bfs(vec![start, end], open_neighbors(&grid))
+            .map(|(_, d)| d)
+            .max();
+        assert_eq!(Some(6), far);
+    }
+
+    #[test]
+    fn test_weighted() {
+        // going right costs more than going down
+        let neighbors = |&(x, y): &(i32, i32)| {
+            let mut next = Vec::new();
+            if x < 3 {
+                next.push(((x + 1, y), 3));
+            }
+            if y < 3 {
+                next.push(((x, y + 1), 1));
+            }
+            next
+        };
+        let costs: HashMap<_, _> = dijkstra(Some((0, 0)), neighbors).collect();
+        assert_eq!(16, costs.len());
+        assert_eq!(Some(&12), costs.get(&(3, 3)));
+        let order: Vec<_> = dijkstra(Some((0, 0)), neighbors).map(|(_, c)| c).collect();
+        assert!(order.windows(2).all(|w| w[0] <= w[1]));
+
+        let (path, cost) = dijkstra_path(Some((0, 0)), neighbors, |&p| p == (3, 3)).unwrap();
+        assert_eq!(12, cost);
+        assert_eq!(7, path.len());
+        let heuristic = |&(x, y): &(i32, i32)| 3 * (3 - x) + (3 - y);
+        let (path, cost) = astar(Some((0, 0)), neighbors, heuristic, |&p| p == (3, 3)).unwrap();
+        assert_eq!((7, 12), (path.len(), cost));
+        assert_eq!(
+            None,
+            astar(Some((0, 0)), neighbors, heuristic, |&p| p == (4, 0))
+        );
+    }
+}